@@ -0,0 +1,31 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use test_rust::lex::{Lex, Token};
+
+/// a source of roughly `size` bytes mixing the tokens the lexer knows about
+fn source(size: usize) -> String {
+    let chunk = "// counter for the loop\n\
+                 let total = 0x1F + 1_000.5e3;\n\
+                 for (let i = 0; i < 10; i++) { total = total * i + 'caf\\u00e9 ☕'; }\n\
+                 let message = `total: ${total} items`;\n\
+                 /* a block comment with ünïcödé */\n";
+    chunk.repeat(size / chunk.len() + 1)
+}
+
+fn lex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lex");
+    for size in [10_000, 100_000, 1_000_000] {
+        let input = source(size);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &input, |b, input| {
+            b.iter(|| {
+                let mut lex = Lex::new(black_box(input));
+                while lex.next() != Token::EOF {}
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, lex);
+criterion_main!(benches);
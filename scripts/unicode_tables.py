@@ -0,0 +1,56 @@
+#!/usr/bin/env python3
+"""Generates src/unicode/tables.rs from the Unicode database bundled with Python.
+
+    python3 scripts/unicode_tables.py > src/unicode/tables.rs
+"""
+import sys
+import unicodedata
+
+# PropList.txt properties that unicodedata does not expose
+OTHER_ID_START = [0x1885, 0x1886, 0x2118, 0x212E, 0x309B, 0x309C]
+OTHER_ID_CONTINUE = [0x00B7, 0x0387, *range(0x1369, 0x1372), 0x19DA]
+# the only Pattern_Syntax code point that would otherwise be a letter
+PATTERN_SYNTAX = [0x2E2F]
+
+START = {"Lu", "Ll", "Lt", "Lm", "Lo", "Nl"}
+CONTINUE = START | {"Mn", "Mc", "Nd", "Pc"}
+
+
+def ranges(pred):
+    out = []
+    for c in range(0x80, sys.maxunicode + 1):
+        if not pred(c):
+            continue
+        if out and out[-1][1] == c - 1:
+            out[-1][1] = c
+        else:
+            out.append([c, c])
+    return out
+
+
+def is_start(c):
+    if c in PATTERN_SYNTAX:
+        return False
+    return c in OTHER_ID_START or unicodedata.category(chr(c)) in START
+
+
+def is_continue(c):
+    if c in PATTERN_SYNTAX:
+        return False
+    return is_start(c) or c in OTHER_ID_CONTINUE or unicodedata.category(chr(c)) in CONTINUE
+
+
+def table(name, rows):
+    print(f"pub const {name}: &[(char, char)] = &[")
+    for a, b in rows:
+        print(f"    ('\\u{{{a:x}}}', '\\u{{{b:x}}}'),")
+    print("];")
+
+
+print(f"// generated by scripts/unicode_tables.py from Unicode {unicodedata.unidata_version}, do not edit")
+print()
+print("/// non-ASCII code points with the ID_Start property")
+table("ID_START", ranges(is_start))
+print()
+print("/// non-ASCII code points with the ID_Continue property")
+table("ID_CONTINUE", ranges(is_continue))
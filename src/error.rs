@@ -0,0 +1,291 @@
+use crate::lex::Token;
+use crate::span::{LineIndex, Span};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+    UnexpectedToken {
+        found: Token<'static>,
+        /// descriptions of what could have been accepted instead, like "`)`" or "expression"
+        expected: Vec<String>,
+        span: Span,
+    },
+    UnterminatedLiteral {
+        /// "string", "template", "comment" ...
+        kind: &'static str,
+        span: Span,
+    },
+    InvalidAssignmentTarget {
+        span: Span,
+    },
+    InvalidTemplateEscape {
+        span: Span,
+    },
+    UnexpectedCharacter {
+        found: char,
+        span: Span,
+    },
+    InvalidEscape {
+        reason: &'static str,
+        span: Span,
+    },
+    InvalidNumber {
+        reason: &'static str,
+        span: Span,
+    },
+    EscapedKeyword {
+        span: Span,
+    },
+    InvalidRegExp {
+        reason: &'static str,
+        span: Span,
+    },
+    InvalidJump {
+        reason: &'static str,
+        span: Span,
+    },
+    UndefinedLabel {
+        name: String,
+        span: Span,
+    },
+    DuplicateLabel {
+        name: String,
+        span: Span,
+    },
+    Redeclaration {
+        name: String,
+        span: Span,
+    },
+    MissingInitializer {
+        span: Span,
+    },
+    MixedCoalesce {
+        span: Span,
+    },
+    NestingTooDeep {
+        span: Span,
+    },
+    UnaryBeforeExponent {
+        span: Span,
+    },
+}
+
+impl ParseError {
+    /// a stable code for tools that match on errors
+    pub fn code(&self) -> &'static str {
+        match self {
+            ParseError::UnexpectedToken { .. } => "E0001",
+            ParseError::UnterminatedLiteral { .. } => "E0002",
+            ParseError::InvalidAssignmentTarget { .. } => "E0003",
+            ParseError::InvalidTemplateEscape { .. } => "E0004",
+            ParseError::UnexpectedCharacter { .. } => "E0005",
+            ParseError::InvalidEscape { .. } => "E0006",
+            ParseError::InvalidNumber { .. } => "E0007",
+            ParseError::EscapedKeyword { .. } => "E0008",
+            ParseError::InvalidRegExp { .. } => "E0009",
+            ParseError::InvalidJump { .. } => "E0010",
+            ParseError::UndefinedLabel { .. } => "E0011",
+            ParseError::DuplicateLabel { .. } => "E0012",
+            ParseError::Redeclaration { .. } => "E0013",
+            ParseError::MissingInitializer { .. } => "E0014",
+            ParseError::MixedCoalesce { .. } => "E0015",
+            ParseError::NestingTooDeep { .. } => "E0016",
+            ParseError::UnaryBeforeExponent { .. } => "E0017",
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::UnexpectedToken { span, .. } => *span,
+            ParseError::UnterminatedLiteral { span, .. } => *span,
+            ParseError::InvalidAssignmentTarget { span } => *span,
+            ParseError::InvalidTemplateEscape { span } => *span,
+            ParseError::UnexpectedCharacter { span, .. } => *span,
+            ParseError::InvalidEscape { span, .. } => *span,
+            ParseError::InvalidNumber { span, .. } => *span,
+            ParseError::EscapedKeyword { span } => *span,
+            ParseError::InvalidRegExp { span, .. } => *span,
+            ParseError::InvalidJump { span, .. } => *span,
+            ParseError::UndefinedLabel { span, .. } => *span,
+            ParseError::DuplicateLabel { span, .. } => *span,
+            ParseError::Redeclaration { span, .. } => *span,
+            ParseError::MissingInitializer { span } => *span,
+            ParseError::MixedCoalesce { span } => *span,
+            ParseError::NestingTooDeep { span } => *span,
+            ParseError::UnaryBeforeExponent { span } => *span,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            ParseError::UnexpectedToken {
+                found, expected, ..
+            } => match expected.as_slice() {
+                [] => format!("unexpected {}", describe(found)),
+                [one] => format!("unexpected {}, expected {one}", describe(found)),
+                _ => format!(
+                    "unexpected {}, expected one of {}",
+                    describe(found),
+                    expected.join(", ")
+                ),
+            },
+            ParseError::UnterminatedLiteral { kind, .. } => format!("unterminated {kind}"),
+            ParseError::InvalidAssignmentTarget { .. } => "invalid assignment target".to_string(),
+            ParseError::InvalidTemplateEscape { .. } => {
+                "invalid escape sequence in template".to_string()
+            }
+            ParseError::UnexpectedCharacter { found, .. } => {
+                format!("unexpected character {found:?}")
+            }
+            ParseError::InvalidEscape { reason, .. } => reason.to_string(),
+            ParseError::InvalidNumber { reason, .. } => format!("invalid number: {reason}"),
+            ParseError::EscapedKeyword { .. } => {
+                "keywords cannot contain escape sequences".to_string()
+            }
+            ParseError::InvalidRegExp { reason, .. } => {
+                format!("invalid regular expression: {reason}")
+            }
+            ParseError::InvalidJump { reason, .. } => reason.to_string(),
+            ParseError::UndefinedLabel { name, .. } => format!("undefined label `{name}`"),
+            ParseError::DuplicateLabel { name, .. } => {
+                format!("label `{name}` is already declared")
+            }
+            ParseError::Redeclaration { name, .. } => {
+                format!("`{name}` has already been declared")
+            }
+            ParseError::MissingInitializer { .. } => {
+                "missing initializer in declaration".to_string()
+            }
+            ParseError::MixedCoalesce { .. } => {
+                "`??` cannot be mixed with `&&` or `||` without parentheses".to_string()
+            }
+            ParseError::NestingTooDeep { .. } => {
+                "statements or expressions are nested too deeply".to_string()
+            }
+            ParseError::UnaryBeforeExponent { .. } => {
+                "a unary expression cannot be the base of `**` without parentheses".to_string()
+            }
+        }
+    }
+
+    /// the short text printed next to the carets
+    fn label(&self) -> String {
+        match self {
+            ParseError::UnexpectedToken { expected, .. } if !expected.is_empty() => {
+                format!("expected {}", expected.join(" or "))
+            }
+            ParseError::UnexpectedToken { .. } => "unexpected token".to_string(),
+            ParseError::UnterminatedLiteral { .. } => "starts here".to_string(),
+            ParseError::InvalidAssignmentTarget { .. } => "cannot be assigned to".to_string(),
+            ParseError::InvalidTemplateEscape { .. } => {
+                "only allowed in tagged templates".to_string()
+            }
+            ParseError::UnexpectedCharacter { .. } => "not valid here".to_string(),
+            ParseError::InvalidEscape { .. } => "invalid escape".to_string(),
+            ParseError::InvalidNumber { .. } => "invalid number".to_string(),
+            ParseError::EscapedKeyword { .. } => "written with an escape".to_string(),
+            ParseError::InvalidRegExp { .. } => "invalid flags".to_string(),
+            ParseError::InvalidJump { .. } => "not allowed here".to_string(),
+            ParseError::UndefinedLabel { .. } => {
+                "no enclosing statement has this label".to_string()
+            }
+            ParseError::DuplicateLabel { .. } => "shadows an enclosing label".to_string(),
+            ParseError::Redeclaration { .. } => "declared again here".to_string(),
+            ParseError::MissingInitializer { .. } => "needs `= value`".to_string(),
+            ParseError::MixedCoalesce { .. } => "wrap this in parentheses".to_string(),
+            ParseError::NestingTooDeep { .. } => "too deep from here on".to_string(),
+            ParseError::UnaryBeforeExponent { .. } => "wrap this in parentheses".to_string(),
+        }
+    }
+
+    /// renders the error rustc-style, quoting the offending line of `source`
+    pub fn render(&self, source: &str, file: &str) -> String {
+        let index = LineIndex::new(source);
+        let span = self.span();
+        let (line, column) = index.line_col(source, span.start);
+        let line_span = index.line_span(source, line);
+        let text = &source[line_span.start..line_span.end];
+        let end = span.end.clamp(span.start, line_span.end);
+        let width = source[span.start.min(end)..end].chars().count().max(1);
+        let gutter = " ".repeat(line.to_string().len());
+        format!(
+            "error[{code}]: {message}\n\
+             {gutter}--> {file}:{line}:{column}\n\
+             {gutter} |\n\
+             {line} | {text}\n\
+             {gutter} | {pad}{carets} {label}\n",
+            code = self.code(),
+            message = self.message(),
+            pad = " ".repeat(column - 1),
+            carets = "^".repeat(width),
+            label = self.label(),
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error[{}]: {}", self.code(), self.message())
+    }
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Punct(p) => format!("`{p}`"),
+        Token::Variable(s) => format!("identifier `{s}`"),
+        Token::Digit(s) => format!("number `{s}`"),
+        Token::String(_) => "string literal".to_string(),
+        Token::Template { .. } => "template literal".to_string(),
+        Token::RegExp { .. } => "regular expression literal".to_string(),
+        Token::EOF => "end of input".to_string(),
+        Token::Error(e) => e.message(),
+        _ => format!("keyword `{}`", token.to_string().to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::lex::Token;
+    use crate::punct::Punct;
+    use crate::span::Span;
+
+    #[test]
+    fn test_render() {
+        let error = ParseError::UnexpectedToken {
+            found: Token::Punct(Punct::Semicolon),
+            expected: vec!["`)`".to_string()],
+            span: Span::new(12, 13),
+        };
+
+        assert_eq!(error.code(), "E0001");
+        assert_eq!(
+            error.to_string(),
+            "error[E0001]: unexpected `;`, expected `)`"
+        );
+        assert_eq!(
+            error.render("a = 1\n  + (b;\n", "a.js"),
+            "error[E0001]: unexpected `;`, expected `)`\n \
+             --> a.js:2:7\n  \
+             |\n\
+             2 |   + (b;\n  \
+             |       ^ expected `)`\n"
+        );
+    }
+
+    #[test]
+    fn test_render_multi_char() {
+        let error = ParseError::InvalidAssignmentTarget {
+            span: Span::new(0, 5),
+        };
+
+        assert_eq!(
+            error.render("a + b = c", "b.js"),
+            "error[E0003]: invalid assignment target\n \
+             --> b.js:1:1\n  \
+             |\n\
+             1 | a + b = c\n  \
+             | ^^^^^ cannot be assigned to\n"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use crate::error::ParseError;
+use crate::exp::function_exp::with_for_init;
+use crate::express::{expect, is_punct, ok_box, parse_expression};
+use crate::node::Node;
+use crate::node::Node::{
+    ArrayExpression, ArrayPattern, AssignmentExpression, AssignmentPattern, Identity,
+    MemberExpression, RestElement, SpreadElement,
+};
+use crate::parser::Parser;
+use crate::punct::Punct;
+use crate::span::Span;
+
+pub fn build_array(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect(parser, Punct::LBracket)?;
+    parser.next();
+    // `in` is an operator again inside the brackets
+    let (elements, trailing_comma) = with_for_init(parser, false, build_elements)?;
+    expect(parser, Punct::RBracket)?;
+    parser.next();
+    ok_box(ArrayExpression {
+        elements,
+        trailing_comma,
+        span: parser.span_from(start),
+    })
+}
+
+/// the elements of an array literal, with `None` for holes
+type Elements = Vec<Option<Box<Node>>>;
+
+/// parses the elements up to the `]` of an array literal, and whether a `,` follows the
+/// last of them
+fn build_elements(parser: &mut Parser) -> Result<(Elements, bool), ParseError> {
+    let mut elements = vec![];
+    let mut trailing_comma = false;
+    while !is_punct(&parser.current, Punct::RBracket) {
+        if is_punct(&parser.current, Punct::Comma) {
+            parser.next();
+            elements.push(None);
+            continue;
+        }
+        let element_start = parser.current_span.start;
+        if is_punct(&parser.current, Punct::Ellipsis) {
+            parser.next();
+            let argument = parse_expression(parser, 2)?;
+            elements.push(Some(Box::new(SpreadElement {
+                argument,
+                span: parser.span_from(element_start),
+            })));
+        } else {
+            elements.push(Some(parse_expression(parser, 2)?));
+        }
+        // a trailing comma adds no hole
+        if !is_punct(&parser.current, Punct::Comma) {
+            break;
+        }
+        parser.next();
+        trailing_comma = is_punct(&parser.current, Punct::RBracket);
+    }
+    Ok((elements, trailing_comma))
+}
+
+/// reinterprets an expression parsed as a cover, like the `[a, b]` of `[a, b] = c`, as a
+/// pattern; only names may be bound in a `binding` pattern, while assignment also allows
+/// member expressions
+pub fn to_pattern(node: Box<Node>, binding: bool) -> Result<Box<Node>, ParseError> {
+    match *node {
+        Identity { .. } => Ok(node),
+        MemberExpression { .. } if !binding => Ok(node),
+        ArrayExpression {
+            elements,
+            trailing_comma,
+            span,
+        } => to_array_pattern(elements, trailing_comma, span, binding),
+        ArrayPattern { elements, span } => to_array_pattern(elements, false, span, binding),
+        AssignmentExpression {
+            operator,
+            left,
+            right,
+            span,
+        } if operator == "=" => ok_box(AssignmentPattern {
+            left: to_pattern(left, binding)?,
+            right,
+            span,
+        }),
+        AssignmentPattern { left, right, span } => ok_box(AssignmentPattern {
+            left: to_pattern(left, binding)?,
+            right,
+            span,
+        }),
+        _ => Err(ParseError::InvalidAssignmentTarget { span: node.span() }),
+    }
+}
+
+/// turns array elements into pattern elements, where a rest element has to come last
+/// and without a `,` after it
+fn to_array_pattern(
+    elements: Vec<Option<Box<Node>>>,
+    trailing_comma: bool,
+    span: Span,
+    binding: bool,
+) -> Result<Box<Node>, ParseError> {
+    let last = elements.len().saturating_sub(1);
+    let elements = elements
+        .into_iter()
+        .enumerate()
+        .map(|(i, element)| match element.map(|element| *element) {
+            None => Ok(None),
+            Some(SpreadElement { argument, span } | RestElement { argument, span })
+                if i == last && !trailing_comma =>
+            {
+                let argument = to_pattern(argument, binding)?;
+                Ok(Some(Box::new(RestElement { argument, span })))
+            }
+            Some(element) => to_pattern(Box::new(element), binding).map(Some),
+        })
+        .collect::<Result<_, _>>()?;
+    ok_box(ArrayPattern { elements, span })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::express_test::test::expressions;
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    #[test]
+    fn test_array() {
+        let mut parser = Parser::new("[1, , 3]; [a, ...b, ]; [,]; []");
+        let nodes = expressions(&mut parser);
+        let shapes = nodes
+            .iter()
+            .map(|node| match &**node {
+                ArrayExpression { elements, .. } => elements
+                    .iter()
+                    .map(|element| match element.as_deref() {
+                        None => "hole",
+                        Some(SpreadElement { .. }) => "spread",
+                        Some(_) => "expression",
+                    })
+                    .collect::<Vec<_>>(),
+                _ => panic!("expect array, find {node:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            shapes,
+            vec![
+                vec!["expression", "hole", "expression"],
+                vec!["expression", "spread"],
+                vec!["hole"],
+                vec![],
+            ]
+        );
+        assert_eq!(nodes[1].span(), Span::new(10, 21));
+    }
+
+    #[test]
+    fn test_array_destructuring() {
+        let mut parser =
+            Parser::new("[a, [b.c], d = 1, ...e] = f; ([g, ...h]) => g; for ([i] of j) ;");
+        let (ast, _) = parser.parse().unwrap();
+        let ExpressionStatement { expression, .. } = &*ast[0] else {
+            panic!("expect expression statement, find {ast:?}");
+        };
+        let AssignmentExpression { left, .. } = &**expression else {
+            panic!("expect assignment, find {expression:?}");
+        };
+        let ArrayPattern { elements, .. } = &**left else {
+            panic!("expect array pattern, find {left:?}");
+        };
+        assert!(matches!(elements[1].as_deref(), Some(ArrayPattern { .. })));
+        assert!(matches!(
+            elements[2].as_deref(),
+            Some(AssignmentPattern { .. })
+        ));
+        assert!(matches!(elements[3].as_deref(), Some(RestElement { .. })));
+
+        let ExpressionStatement { expression, .. } = &*ast[1] else {
+            panic!("expect expression statement, find {ast:?}");
+        };
+        let ArrowFunctionExpression { params, .. } = &**expression else {
+            panic!("expect arrow function, find {expression:?}");
+        };
+        assert!(matches!(*params[0], ArrayPattern { .. }));
+
+        let ForOfStatement { left, .. } = &*ast[2] else {
+            panic!("expect for-of statement, find {ast:?}");
+        };
+        assert!(matches!(**left, ArrayPattern { .. }));
+    }
+
+    #[test]
+    fn test_array_pattern_errors() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert_eq!(
+            error("[a + 1] = b"),
+            ParseError::InvalidAssignmentTarget {
+                span: Span::new(1, 6),
+            }
+        );
+        assert!(matches!(
+            error("[...a, b] = c"),
+            ParseError::InvalidAssignmentTarget { .. }
+        ));
+        assert_eq!(
+            error("[...a,] = b"),
+            ParseError::InvalidAssignmentTarget {
+                span: Span::new(1, 5),
+            }
+        );
+        assert!(matches!(
+            error("let [...a,] = b"),
+            ParseError::InvalidAssignmentTarget { .. }
+        ));
+        // parameters bind names, so member expressions do not fit
+        assert!(matches!(
+            error("([a.b]) => a"),
+            ParseError::InvalidAssignmentTarget { .. }
+        ));
+        assert!(matches!(
+            error("([a, a]) => a"),
+            ParseError::Redeclaration { .. }
+        ));
+    }
+}
@@ -0,0 +1,274 @@
+use crate::error::ParseError;
+use crate::exp::array_exp::to_pattern;
+use crate::exp::declaration_exp::bound_names;
+use crate::exp::function_exp::{build_function_body, in_function_scope, with_for_init};
+use crate::express::{expect, is_punct, ok_box, parse_expression};
+use crate::node::Node;
+use crate::node::Node::{ArrowFunctionExpression, RestElement, SequenceExpression, SpreadElement};
+use crate::parser::Parser;
+use crate::punct::Punct;
+use crate::span::Span;
+use std::collections::HashSet;
+
+/// `( ... )` parsed before knowing whether it groups an expression or holds the
+/// parameters of an arrow function
+pub struct CoverParen {
+    items: Vec<Box<Node>>,
+    /// what makes this invalid as an expression, like `()` or `(a, ...b)`
+    not_expression: Option<ParseError>,
+}
+
+pub fn parse_cover_paren(parser: &mut Parser) -> Result<CoverParen, ParseError> {
+    expect(parser, Punct::LParen)?;
+    parser.next();
+    let (items, not_expression) = with_for_init(parser, false, parse_cover_items)?;
+    expect(parser, Punct::RParen)?;
+    parser.next();
+    Ok(CoverParen {
+        items,
+        not_expression,
+    })
+}
+
+/// parses the comma separated items up to the `)` of a cover
+fn parse_cover_items(
+    parser: &mut Parser,
+) -> Result<(Vec<Box<Node>>, Option<ParseError>), ParseError> {
+    let mut items = vec![];
+    let mut not_expression = None;
+    loop {
+        if is_punct(&parser.current, Punct::RParen) {
+            // `()` and a trailing comma only fit parameters
+            not_expression.get_or_insert_with(|| parser.unexpected(&["expression"]));
+            break;
+        }
+        if is_punct(&parser.current, Punct::Ellipsis) {
+            not_expression.get_or_insert_with(|| parser.unexpected(&["expression"]));
+            let rest_start = parser.current_span.start;
+            parser.next();
+            let argument = parse_expression(parser, 2)?;
+            items.push(Box::new(RestElement {
+                argument,
+                span: parser.span_from(rest_start),
+            }));
+            break;
+        }
+        items.push(parse_expression(parser, 2)?);
+        if !is_punct(&parser.current, Punct::Comma) {
+            break;
+        }
+        parser.next();
+    }
+    Ok((items, not_expression))
+}
+
+impl CoverParen {
+    /// the parenthesized expression, a `SequenceExpression` for `(a, b)`
+    pub fn into_expression(mut self) -> Result<Box<Node>, ParseError> {
+        if let Some(error) = self.not_expression {
+            return Err(error);
+        }
+        if self.items.len() == 1 {
+            return Ok(self.items.pop().unwrap());
+        }
+        let start = self.items[0].span().start;
+        let end = self.items[self.items.len() - 1].span().end;
+        ok_box(SequenceExpression {
+            expressions: self.items,
+            span: Span::new(start, end),
+        })
+    }
+
+    /// the arguments of `async(...)` when no `=>` follows, where `...a` spreads
+    pub fn into_arguments(self) -> Vec<Box<Node>> {
+        self.items
+            .into_iter()
+            .map(|item| match *item {
+                RestElement { argument, span } => Box::new(SpreadElement { argument, span }),
+                _ => item,
+            })
+            .collect()
+    }
+
+    /// reinterprets the items as parameters, like `a = 1` as a default value
+    pub fn into_params(self) -> Result<Vec<Box<Node>>, ParseError> {
+        self.items.into_iter().map(to_param).collect()
+    }
+}
+
+fn to_param(item: Box<Node>) -> Result<Box<Node>, ParseError> {
+    match *item {
+        RestElement { argument, span } => ok_box(RestElement {
+            argument: to_pattern(argument, true)?,
+            span,
+        }),
+        _ => to_pattern(item, true),
+    }
+}
+
+/// whether the current token is an `=>` on the same line as the parameters
+pub fn at_arrow(parser: &Parser) -> bool {
+    is_punct(&parser.current, Punct::Arrow) && !parser.newline_before
+}
+
+/// parses `=> body` for parameters that start at `start`
+pub fn build_arrow(
+    parser: &mut Parser,
+    start: usize,
+    params: Vec<Box<Node>>,
+    is_async: bool,
+) -> Result<Box<Node>, ParseError> {
+    expect(parser, Punct::Arrow)?;
+    parser.next();
+    let (body, expression) = in_function_scope(parser, is_async, false, |parser| {
+        // unlike in ordinary functions, arrow parameters may not repeat
+        let mut names = vec![];
+        for param in &params {
+            bound_names(param, &mut names);
+        }
+        let mut seen = HashSet::new();
+        for (name, span) in names {
+            if !seen.insert(name) {
+                return Err(ParseError::Redeclaration {
+                    name: name.to_string(),
+                    span,
+                });
+            }
+            parser.scopes.declare_var(name, span)?;
+        }
+        if is_punct(&parser.current, Punct::LBrace) {
+            Ok((build_function_body(parser)?, false))
+        } else {
+            Ok((parse_expression(parser, 2)?, true))
+        }
+    })?;
+    ok_box(ArrowFunctionExpression {
+        params,
+        body,
+        expression,
+        is_async,
+        span: parser.span_from(start),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::express_test::test::expressions;
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    #[test]
+    fn test_arrow() {
+        let mut parser = Parser::new("x => x; (a, b = 1, ...c) => {}; () => a + b");
+        let nodes = expressions(&mut parser);
+        let shapes = nodes
+            .iter()
+            .map(|node| match &**node {
+                ArrowFunctionExpression {
+                    params, expression, ..
+                } => (params.len(), *expression),
+                _ => panic!("expect arrow function, find {node:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(shapes, vec![(1, true), (3, false), (0, true)]);
+        assert_eq!(nodes[2].span(), Span::new(32, 43));
+    }
+
+    #[test]
+    fn test_async_arrow() {
+        let mut parser = Parser::new("async x => await x; async (a) => a; async(a, b)");
+        let nodes = expressions(&mut parser);
+        let ArrowFunctionExpression {
+            body,
+            is_async: true,
+            ..
+        } = &*nodes[0]
+        else {
+            panic!("expect async arrow function, find {:?}", nodes[0]);
+        };
+        assert!(matches!(**body, AwaitExpression { .. }));
+        assert!(matches!(
+            *nodes[1],
+            ArrowFunctionExpression { is_async: true, .. }
+        ));
+        let CallExpression { arguments, .. } = &*nodes[2] else {
+            panic!("expect call, find {:?}", nodes[2]);
+        };
+        assert_eq!(arguments.len(), 2);
+    }
+
+    #[test]
+    fn test_contextual_keyword_param() {
+        let mut parser = Parser::new("async => async; f(async => 1)");
+        let nodes = expressions(&mut parser);
+        let ArrowFunctionExpression {
+            params,
+            body,
+            is_async: false,
+            ..
+        } = &*nodes[0]
+        else {
+            panic!("expect arrow function, find {:?}", nodes[0]);
+        };
+        assert!(matches!(&*params[0], Identity { name, .. } if name == "async"));
+        assert!(matches!(&**body, Identity { name, .. } if name == "async"));
+        let CallExpression { arguments, .. } = &*nodes[1] else {
+            panic!("expect call, find {:?}", nodes[1]);
+        };
+        assert!(matches!(*arguments[0], ArrowFunctionExpression { .. }));
+    }
+
+    #[test]
+    fn test_arrow_as_value() {
+        let mut parser = Parser::new("f = x => x; cb = () => {}; x = async () => 1");
+        let nodes = expressions(&mut parser);
+        let values = nodes
+            .iter()
+            .map(|node| match &**node {
+                AssignmentExpression { right, .. } => match &**right {
+                    ArrowFunctionExpression { is_async, .. } => *is_async,
+                    _ => panic!("expect arrow function, find {right:?}"),
+                },
+                _ => panic!("expect assignment, find {node:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(values, vec![false, false, true]);
+    }
+
+    #[test]
+    fn test_paren_expression() {
+        let mut parser = Parser::new("(a, b); (x => x)(1)");
+        let nodes = expressions(&mut parser);
+        assert!(matches!(*nodes[0], SequenceExpression { .. }));
+        assert!(matches!(*nodes[1], CallExpression { .. }));
+    }
+
+    #[test]
+    fn test_arrow_errors() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert_eq!(
+            error("(a + 1) => a"),
+            ParseError::InvalidAssignmentTarget {
+                span: Span::new(1, 6),
+            }
+        );
+        assert!(matches!(
+            error("(a, a) => a"),
+            ParseError::Redeclaration { .. }
+        ));
+        assert!(matches!(error("()"), ParseError::UnexpectedToken { .. }));
+        assert!(matches!(
+            error("(a, ...b)"),
+            ParseError::UnexpectedToken { .. }
+        ));
+        assert!(matches!(
+            error("a + async x => x"),
+            ParseError::UnexpectedToken { span, .. } if span == Span::new(10, 11)
+        ));
+        // the arrow has to be on the same line as the parameters
+        assert!(error("(a)\n=> a").span() == Span::new(4, 6));
+    }
+}
@@ -1,65 +1,157 @@
-use crate::express::{expect_keys, is_ctrl_word, parse_expression};
+use crate::error::ParseError;
+use crate::exp::array_exp::{build_array, to_pattern};
+use crate::express::{expect_keys, is_punct, parse_expression};
 use crate::lex::Token;
 use crate::node::Node;
-use crate::node::Node::{VariableDeclaration, VariableDeclarator};
+use crate::node::Node::{
+    ArrayPattern, AssignmentPattern, RestElement, VariableDeclaration, VariableDeclarator,
+};
 use crate::parser::Parser;
+use crate::punct::Punct;
+use crate::span::Span;
 
-pub fn build_let(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let kind = expect_keys(&parser.current, &vec![Token::Var, Token::Let])?;
+pub fn build_let(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let kind = expect_keys(parser, &vec![Token::Var, Token::Let, Token::Const])?;
     parser.next();
     let mut declarations = vec![];
-    declarations.push(build_declarator(parser)?);
+    declarations.push(build_declarator(parser, &kind)?);
     loop {
         let c2 = &parser.current;
         match c2 {
-            Token::Control(s) => match s.as_str() {
-                "," => {
-                    parser.next();
-                    declarations.push(build_declarator(parser)?);
-                }
-                "\r" | "\n" => {
-                    parser.next();
-                }
-                _ => break,
-            },
+            Token::Punct(Punct::Comma) => {
+                parser.next();
+                declarations.push(build_declarator(parser, &kind)?);
+            }
             _ => break,
         }
     }
-    Ok(Box::new(VariableDeclaration { kind, declarations }))
+    let declaration = VariableDeclaration {
+        kind: kind.into_owned(),
+        declarations,
+        span: parser.span_from(start),
+    };
+    // the head of a for-in or for-of loop is the only place without initializers
+    if !parser.in_for_init {
+        check_initializers(&declaration)?;
+    }
+    Ok(Box::new(declaration))
+}
+
+/// reports a `const` or a destructuring declarator without `= value`
+pub fn check_initializers(declaration: &Node) -> Result<(), ParseError> {
+    let VariableDeclaration {
+        kind, declarations, ..
+    } = declaration
+    else {
+        return Ok(());
+    };
+    for declarator in declarations {
+        let VariableDeclarator { id, span, .. } = &**declarator else {
+            continue;
+        };
+        let needs_init = *kind == Token::Const || matches!(**id, ArrayPattern { .. });
+        if needs_init && id.span() == *span {
+            return Err(ParseError::MissingInitializer { span: *span });
+        }
+    }
+    Ok(())
 }
 
-fn build_declarator(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let id = &parser.current;
-    if let Token::Variable(s) = id {
-        let id = Box::new(Node::Identity {
-            name: s.to_string(),
-        });
-        parser.next();
-        let equal = &parser.current;
-        if !is_ctrl_word(equal, "=") {
-            return Ok(Box::new(VariableDeclarator {
-                id,
-                init: Box::new(Node::Identity {
-                    name: "undefined".to_string(),
-                }),
-            }));
+/// adds the names bound by `id` to the current scope
+pub fn declare_names(parser: &mut Parser, kind: &Token, id: &Node) -> Result<(), ParseError> {
+    let mut names = vec![];
+    bound_names(id, &mut names);
+    for (name, span) in names {
+        if *kind == Token::Var {
+            parser.scopes.declare_var(name, span)?;
+        } else {
+            parser.scopes.declare_lexical(name, span)?;
         }
-        parser.next();
+    }
+    Ok(())
+}
+
+/// collects the identifiers a pattern binds, like `a` and `b` of `[a, b = 1]`
+pub fn bound_names<'a>(id: &'a Node, names: &mut Vec<(&'a str, Span)>) {
+    match id {
+        Node::Identity { name, span } => names.push((name, *span)),
+        ArrayPattern { elements, .. } => {
+            for element in elements.iter().flatten() {
+                bound_names(element, names);
+            }
+        }
+        AssignmentPattern { left, .. } => bound_names(left, names),
+        RestElement { argument, .. } => bound_names(argument, names),
+        _ => {}
+    }
+}
+
+fn build_declarator(parser: &mut Parser, kind: &Token) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    // only `var` may bind the name `let`
+    if *kind != Token::Var && parser.current == Token::Let {
+        return Err(parser.unexpected(&["identifier"]));
+    }
+    let id = build_binding(parser)?;
+    declare_names(parser, kind, &id)?;
+    let equal = &parser.current;
+    if !is_punct(equal, Punct::Eq) {
         return Ok(Box::new(VariableDeclarator {
             id,
-            init: parse_expression(parser, 1)?,
+            init: Box::new(Node::Identity {
+                name: "undefined".to_string(),
+                span: Span::new(parser.last_end, parser.last_end),
+            }),
+            span: parser.span_from(start),
         }));
     }
-    Err(format!("expect Variable, find {id}"))
+    parser.next();
+    let init = parse_expression(parser, 2)?;
+    Ok(Box::new(VariableDeclarator {
+        id,
+        init,
+        span: parser.span_from(start),
+    }))
+}
+
+/// parses the name a declaration binds, an identifier or an array pattern like `[k, v]`
+pub fn build_binding(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    match &parser.current {
+        // the array literal is the cover grammar of the pattern
+        Token::Punct(Punct::LBracket) => to_pattern(build_array(parser)?, true),
+        _ => build_binding_identifier(parser),
+    }
+}
+
+/// parses a bound identifier, which may also be a contextual keyword where it is no operator
+pub fn build_binding_identifier(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let name = match &parser.current {
+        Token::Variable(s) => s.to_string(),
+        word @ (Token::Async | Token::Let | Token::Await | Token::Yield)
+            if (*word != Token::Await || !parser.in_async)
+                && (*word != Token::Yield || !parser.in_generator) =>
+        {
+            word.to_string().to_lowercase()
+        }
+        _ => return Err(parser.unexpected(&["identifier"])),
+    };
+    let id = Box::new(Node::Identity {
+        name,
+        span: parser.current_span,
+    });
+    parser.next();
+    Ok(id)
 }
 
 #[cfg(test)]
 mod test_let {
     use super::*;
+    use crate::error::ParseError;
 
     #[test]
     fn test() {
-        let mut parser = Parser::new("let a = 1".to_string());
+        let mut parser = Parser::new("let a = 1");
 
         let result = build_let(&mut parser);
         println!("{result:#?}");
@@ -67,17 +159,156 @@ mod test_let {
 
     #[test]
     fn test_express() {
-        let mut parser = Parser::new("let a = 1 + 2".to_string());
+        let mut parser = Parser::new("let a = 1 + 2");
 
         let result = build_let(&mut parser);
         println!("{result:#?}");
     }
 
+    #[test]
+    fn test_array_pattern() {
+        let mut parser = Parser::new("const [, a, [b]] = c");
+
+        let result = build_let(&mut parser).unwrap();
+        let VariableDeclaration { declarations, .. } = *result else {
+            panic!("expect declaration, find {result:?}");
+        };
+        let VariableDeclarator { id, .. } = &*declarations[0] else {
+            panic!("expect declarator, find {declarations:?}");
+        };
+        let ArrayPattern { elements, span } = &**id else {
+            panic!("expect array pattern, find {id:?}");
+        };
+        assert_eq!(*span, Span::new(6, 16));
+        assert!(elements[0].is_none());
+        assert!(matches!(elements[2].as_deref(), Some(ArrayPattern { .. })));
+    }
+
+    #[test]
+    fn test_const() {
+        let mut parser = Parser::new("const a = 1, b = a");
+        assert!(build_let(&mut parser).is_ok());
+
+        let mut parser = Parser::new("const a = 1, b;");
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::MissingInitializer {
+                span: Span::new(13, 14),
+            })
+        );
+        let mut parser = Parser::new("let [a];");
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::MissingInitializer { .. })
+        ));
+        let mut parser = Parser::new("for (const a;;) ;");
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::MissingInitializer { .. })
+        ));
+        let mut parser = Parser::new("for (const a of b) ;");
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn test_redeclaration() {
+        let error = |input| Parser::new(input).parse().err();
+
+        assert_eq!(
+            error("let a; let a;"),
+            Some(ParseError::Redeclaration {
+                name: "a".to_string(),
+                span: Span::new(11, 12),
+            })
+        );
+        assert!(error("var a; let a;").is_some());
+        assert!(error("let a; { var a; }").is_some());
+        assert!(error("{ var a; } const a = 1;").is_some());
+        assert!(error("let [a, [a]] = b;").is_some());
+        assert!(error("switch (a) { case 1: let b; default: let b; }").is_some());
+        // blocks and loop heads get their own scope, and `var` may repeat
+        assert_eq!(
+            error("var a; var a; { let a; } for (let a;;) { let a; }"),
+            None
+        );
+        assert_eq!(error("{ let a; } { let a; }"), None);
+    }
+
+    #[test]
+    fn test_contextual_keyword_names() {
+        let mut parser =
+            Parser::new("let async = 1; var [await, yield] = a; function f(async) {} var let = 1");
+        let (ast, _) = parser.parse().unwrap();
+        let VariableDeclaration { declarations, .. } = &*ast[0] else {
+            panic!("expect declaration, find {ast:?}");
+        };
+        let VariableDeclarator { id, .. } = &*declarations[0] else {
+            panic!("expect declarator, find {declarations:?}");
+        };
+        assert_eq!(
+            **id,
+            Node::Identity {
+                name: "async".to_string(),
+                span: Span::new(4, 9),
+            }
+        );
+        let VariableDeclaration { declarations, .. } = &*ast[3] else {
+            panic!("expect declaration, find {:?}", ast[3]);
+        };
+        let VariableDeclarator { id, .. } = &*declarations[0] else {
+            panic!("expect declarator, find {declarations:?}");
+        };
+        assert!(matches!(&**id, Node::Identity { name, .. } if name == "let"));
+        let (ast, _) = Parser::new("function async() {}").parse().unwrap();
+        let Node::FunctionDeclaration { id, .. } = &*ast[0] else {
+            panic!("expect function declaration, find {ast:?}");
+        };
+        assert!(matches!(&**id, Node::Identity { name, .. } if name == "async"));
+
+        let error = |input| Parser::new(input).parse().err();
+        assert!(error("async function f() { let await = 1 }").is_some());
+        assert!(error("function* g() { let yield = 1 }").is_some());
+        assert_eq!(error("function f() { let yield, await }"), None);
+        assert!(error("let let = 1").is_some());
+        assert!(error("async function f() { function await() {} }").is_some());
+    }
+
+    #[test]
+    fn test_pattern_defaults_and_rest() {
+        let mut parser = Parser::new("let [a = 1, [b], ...rest] = arr; function f([c = 1]) {}");
+        let (ast, _) = parser.parse().unwrap();
+        let VariableDeclaration { declarations, .. } = &*ast[0] else {
+            panic!("expect declaration, find {ast:?}");
+        };
+        let VariableDeclarator { id, .. } = &*declarations[0] else {
+            panic!("expect declarator, find {declarations:?}");
+        };
+        let ArrayPattern { elements, .. } = &**id else {
+            panic!("expect array pattern, find {id:?}");
+        };
+        assert!(matches!(
+            elements[0].as_deref(),
+            Some(AssignmentPattern { .. })
+        ));
+        assert!(matches!(elements[2].as_deref(), Some(RestElement { .. })));
+        let Node::FunctionDeclaration { params, .. } = &*ast[1] else {
+            panic!("expect function, find {ast:?}");
+        };
+        assert!(matches!(*params[0], ArrayPattern { .. }));
+
+        // the names of a pattern are declared like plain ones
+        let mut parser = Parser::new("let [a, ...a] = b");
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::Redeclaration { .. })
+        ));
+    }
+
     #[test]
     fn test_comma() {
-        let mut parser = Parser::new("let a = 3, b = 2".to_string());
+        let mut parser = Parser::new("let a = 3, b = 2");
 
         let result = build_let(&mut parser);
         println!("{result:#?}");
     }
-}
\ No newline at end of file
+}
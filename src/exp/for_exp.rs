@@ -1,76 +1,173 @@
-use crate::exp::declaration_exp::build_let;
-use crate::express::{expect, expect_keyword, is_ctrl_word, ok_box, parse_expression};
+use crate::error::ParseError;
+use crate::exp::array_exp::to_pattern;
+use crate::exp::declaration_exp::{build_let, check_initializers};
+use crate::exp::function_exp::with_for_init;
+use crate::exp::statement_exp::parse_loop_body;
+use crate::express::{expect, expect_keyword, is_punct, ok_box, parse_expression};
 use crate::lex::Token;
 use crate::node::Node;
-use crate::node::Node::{EmptyStatement, ForStatement};
+use crate::node::Node::{
+    EmptyStatement, ForInStatement, ForOfStatement, ForStatement, VariableDeclaration,
+    VariableDeclarator,
+};
 use crate::parser::Parser;
+use crate::punct::Punct;
+use crate::span::Span;
 
-pub fn build_for(parser: &mut Parser) -> Result<Box<Node>, String> {
-    let init: Box<Node>;
-    let test: Box<Node>;
-    let update: Box<Node>;
-    let mut body: Box<Vec<Box<Node>>> = Box::new(vec![]);
-    expect_keyword(&parser.current, Token::For)?;
+pub fn build_for(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect_keyword(parser, Token::For)?;
     parser.next();
-    expect(&parser.current, "(")?;
+    // only an async function may `for await`, elsewhere the `(` is missing
+    let is_await = parser.current == Token::Await && parser.in_async;
+    if is_await {
+        parser.next();
+    }
+    expect(parser, Punct::LParen)?;
     parser.next();
-    let part1 = &parser.current;
-    if *part1 == Token::Let {
-        init = build_let(parser)?;
-    } else if is_ctrl_word(&part1, ";") {
-        init = Box::new(EmptyStatement {});
-    } else {
-        init = parse_expression(parser, 0)?;
+    // a `let` in the head is scoped to the loop
+    parser.scopes.enter(false);
+    let node = build_for_head(parser, start, is_await);
+    parser.scopes.exit();
+    node
+}
+
+fn build_for_head(
+    parser: &mut Parser,
+    start: usize,
+    is_await: bool,
+) -> Result<Box<Node>, ParseError> {
+    let init = with_for_init(parser, true, |parser| {
+        if matches!(parser.current, Token::Var | Token::Const)
+            || (parser.current == Token::Let && parser.at_let_declaration())
+        {
+            build_let(parser)
+        } else if is_punct(&parser.current, Punct::Semicolon) {
+            ok_box(EmptyStatement {
+                span: Span::new(parser.current_span.start, parser.current_span.start),
+            })
+        } else {
+            parse_expression(parser, 0)
+        }
+    })?;
+
+    let is_of = matches!(&parser.current, Token::Variable(s) if s == "of");
+    if is_await && !is_of {
+        return Err(parser.unexpected(&["`of`"]));
+    }
+    if is_of || parser.current == Token::In {
+        return build_for_in_of(parser, start, init, is_await, is_of);
+    }
+    build_for_classic(parser, start, init)
+}
+
+/// parses the rest of `for (left in right) body` or `for (left of right) body`
+fn build_for_in_of(
+    parser: &mut Parser,
+    start: usize,
+    init: Box<Node>,
+    is_await: bool,
+    is_of: bool,
+) -> Result<Box<Node>, ParseError> {
+    let left = for_in_left(init)?;
+    parser.next();
+    // `of` takes an assignment expression, `in` a full expression
+    let right = parse_expression(parser, if is_of { 2 } else { 1 })?;
+    expect(parser, Punct::RParen)?;
+    parser.next();
+    let body = parse_loop_body(parser)?;
+    let span = parser.span_from(start);
+    if is_of {
+        return ok_box(ForOfStatement {
+            left,
+            right,
+            body,
+            is_await,
+            span,
+        });
     }
+    ok_box(ForInStatement {
+        left,
+        right,
+        body,
+        span,
+    })
+}
 
-    expect(&parser.current, ";")?;
+/// parses the rest of `for (init; test; update) body`
+fn build_for_classic(
+    parser: &mut Parser,
+    start: usize,
+    init: Box<Node>,
+) -> Result<Box<Node>, ParseError> {
+    check_initializers(&init)?;
+    expect(parser, Punct::Semicolon)?;
     parser.next();
     let part2 = &parser.current;
-    if is_ctrl_word(&part2, ";") {
-        test = Box::new(EmptyStatement {});
+    let test = if is_punct(part2, Punct::Semicolon) {
+        Box::new(EmptyStatement {
+            span: Span::new(parser.current_span.start, parser.current_span.start),
+        })
     } else {
-        test = parse_expression(parser, 0)?;
-    }
+        parse_expression(parser, 0)?
+    };
 
-    expect(&parser.current, ";")?;
+    expect(parser, Punct::Semicolon)?;
     parser.next();
     let part3 = &parser.current;
-    if is_ctrl_word(&part3, ")") {
-        update = Box::new(EmptyStatement {});
+    let update = if is_punct(part3, Punct::RParen) {
+        Box::new(EmptyStatement {
+            span: Span::new(parser.current_span.start, parser.current_span.start),
+        })
     } else {
-        update = parse_expression(parser, 0)?;
-    }
+        parse_expression(parser, 0)?
+    };
 
-    expect(&parser.current, ")")?;
+    expect(parser, Punct::RParen)?;
     parser.next();
-    if is_ctrl_word(&parser.current, "{") {
-        parser.next();
-        if !is_ctrl_word(&parser.current, "}") {
-            body.push(parse_expression(parser, 0)?);
-        }
-        expect(&parser.current, "}")?;
-        parser.next();
-    } else if is_ctrl_word(&parser.current, ";") {
-        parser.next();
-    } else {
-        return Err("for body error".to_string());
-    }
+    let body = parse_loop_body(parser)?;
     ok_box(ForStatement {
         init,
         test,
         update,
         body,
+        span: parser.span_from(start),
     })
 }
 
+/// checks the part before `in` or `of`, a single declaration without an initializer
+/// or an assignment target
+fn for_in_left(init: Box<Node>) -> Result<Box<Node>, ParseError> {
+    match &*init {
+        VariableDeclaration { declarations, .. } => {
+            if let [declarator] = declarations.as_slice()
+                && let VariableDeclarator { id, span, .. } = &**declarator
+                && id.span() == *span
+            {
+                return Ok(init);
+            }
+            Err(ParseError::InvalidAssignmentTarget { span: init.span() })
+        }
+        Node::Identity { .. } | Node::MemberExpression { .. } => Ok(init),
+        Node::ArrayExpression { .. } => to_pattern(init, false),
+        _ => Err(ParseError::InvalidAssignmentTarget { span: init.span() }),
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use crate::error::ParseError;
     use crate::lex::Token;
+    use crate::node::Node::{
+        BinaryExpression, BlockStatement, ForInStatement, ForOfStatement, ForStatement,
+        FunctionDeclaration, SequenceExpression,
+    };
     use crate::parser::Parser;
+    use crate::span::Span;
 
     #[test]
     fn test_for() {
-        let mut parser = Parser::new("for(let i =1; i < 10;i++) {}".to_string());
+        let mut parser = Parser::new("for(let i =1; i < 10;i++) {}");
         let ast = parser.parse();
         println!("{ast:#?}");
         assert_eq!(parser.current, Token::EOF)
@@ -78,15 +175,117 @@ mod test {
 
     #[test]
     fn test_for_empty() {
-        let mut parser = Parser::new("for(let i =1; i < 10;i++);".to_string());
-        let ast = parser.parse();
+        let mut parser = Parser::new("for(let i =1; i < 10;i++);");
+        let _ast = parser.parse();
         assert_eq!(parser.current, Token::EOF)
     }
 
     #[test]
     fn test_for_empty2() {
-        let mut parser = Parser::new("for(let i =1; i < 10;i++);".to_string());
-        let ast = parser.parse();
+        let mut parser = Parser::new("for(let i =1; i < 10;i++);");
+        let _ast = parser.parse();
         assert_eq!(parser.current, Token::EOF)
     }
+
+    #[test]
+    fn test_for_block() {
+        let mut parser = Parser::new("for(;;) { a = 1; { b() } c++ }");
+        let (ast, _) = parser.parse().unwrap();
+        let ForStatement { body, .. } = &*ast[0] else {
+            panic!("expect for statement, find {ast:?}");
+        };
+        let BlockStatement { body, .. } = &**body else {
+            panic!("expect block, find {body:?}");
+        };
+        assert_eq!(body.len(), 3);
+    }
+
+    #[test]
+    fn test_for_sequence() {
+        let mut parser = Parser::new("for (i = 0, j = n; i < j; i++, j--) ;");
+        let (ast, _) = parser.parse().unwrap();
+        let ForStatement { init, update, .. } = &*ast[0] else {
+            panic!("expect for statement, find {ast:?}");
+        };
+        assert!(matches!(**init, SequenceExpression { .. }));
+        assert_eq!(update.span(), Span::new(26, 34));
+        assert!(matches!(**update, SequenceExpression { .. }));
+    }
+
+    #[test]
+    fn test_for_in_of() {
+        let mut parser = Parser::new("for (x in obj) ; for (const [k, v] of map) {}");
+        let (ast, _) = parser.parse().unwrap();
+        assert!(matches!(*ast[0], ForInStatement { .. }));
+        assert!(matches!(
+            *ast[1],
+            ForOfStatement {
+                is_await: false,
+                ..
+            }
+        ));
+        assert_eq!(ast[1].span(), Span::new(17, 45));
+
+        let mut parser = Parser::new("async function f() { for await (let a of b) c() }");
+        let (ast, _) = parser.parse().unwrap();
+        let FunctionDeclaration { body, .. } = &*ast[0] else {
+            panic!("expect function, find {ast:?}");
+        };
+        let BlockStatement { body, .. } = &**body else {
+            panic!("expect body, find {body:?}");
+        };
+        assert!(matches!(*body[0], ForOfStatement { is_await: true, .. }));
+    }
+
+    #[test]
+    fn test_for_in_disambiguation() {
+        // `in` inside parentheses is an operator again
+        let mut parser = Parser::new("for (var a = (b in c); a;) ; for (a in b in c) ;");
+        let (ast, _) = parser.parse().unwrap();
+        assert!(matches!(*ast[0], ForStatement { .. }));
+        let ForInStatement { right, .. } = &*ast[1] else {
+            panic!("expect for-in statement, find {ast:?}");
+        };
+        assert!(matches!(**right, BinaryExpression { .. }));
+    }
+
+    #[test]
+    fn test_for_in_errors() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert_eq!(
+            error("for (a + b in c) ;"),
+            ParseError::InvalidAssignmentTarget {
+                span: Span::new(5, 10),
+            }
+        );
+        assert_eq!(
+            error("for (let a, b of c) ;"),
+            ParseError::InvalidAssignmentTarget {
+                span: Span::new(5, 13),
+            }
+        );
+        assert!(matches!(
+            error("async function f() { for await (a in b) ; }"),
+            ParseError::UnexpectedToken {
+                found: Token::In,
+                ..
+            }
+        ));
+        assert_eq!(
+            error("function f() { for await (const x of y) {} }"),
+            ParseError::UnexpectedToken {
+                found: Token::Await,
+                expected: vec!["`(`".to_string()],
+                span: Span::new(19, 24),
+            }
+        );
+        assert!(matches!(
+            error("async function f() { () => { for await (x of y) ; } }"),
+            ParseError::UnexpectedToken {
+                found: Token::Await,
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,388 @@
+use crate::error::ParseError;
+use crate::exp::declaration_exp::{build_binding, build_binding_identifier, declare_names};
+use crate::exp::statement_exp::parse_statement_list;
+use crate::express::{expect, expect_keyword, is_punct, ok_box, parse_expression};
+use crate::lex::Token;
+use crate::node::Node;
+use crate::node::Node::{
+    AssignmentPattern, BlockStatement, FunctionDeclaration, FunctionExpression, Identity,
+    RestElement, ReturnStatement,
+};
+use crate::parser::Parser;
+use crate::punct::Punct;
+
+/// parses `async function* name(params) { body }`, where only a declaration requires the name
+pub fn build_function(parser: &mut Parser, declaration: bool) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let is_async = parser.current == Token::Async;
+    if is_async {
+        parser.next();
+    }
+    expect_keyword(parser, Token::Function)?;
+    parser.next();
+    let is_generator = is_punct(&parser.current, Punct::Star);
+    if is_generator {
+        parser.next();
+    }
+    let id = match &parser.current {
+        Token::Punct(Punct::LParen) if !declaration => None,
+        _ => Some(build_binding_identifier(parser)?),
+    };
+    if declaration && let Some(Identity { name, span }) = id.as_deref() {
+        parser.scopes.declare_function(name, *span)?;
+    }
+
+    let (params, body) = in_function_scope(parser, is_async, is_generator, |parser| {
+        let params = build_params(parser)?;
+        let body = build_function_body(parser)?;
+        Ok((params, body))
+    })?;
+
+    let span = parser.span_from(start);
+    match id {
+        Some(id) if declaration => ok_box(FunctionDeclaration {
+            id,
+            params,
+            body,
+            is_async,
+            is_generator,
+            span,
+        }),
+        id => ok_box(FunctionExpression {
+            id,
+            params,
+            body,
+            is_async,
+            is_generator,
+            span,
+        }),
+    }
+}
+
+/// parses the `(params) { body }` of a method, which has no name of its own
+pub fn build_method(
+    parser: &mut Parser,
+    is_async: bool,
+    is_generator: bool,
+) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let (params, body) = in_function_scope(parser, is_async, is_generator, |parser| {
+        let params = build_params(parser)?;
+        let body = build_function_body(parser)?;
+        Ok((params, body))
+    })?;
+    ok_box(FunctionExpression {
+        id: None,
+        params,
+        body,
+        is_async,
+        is_generator,
+        span: parser.span_from(start),
+    })
+}
+
+/// runs `parse` in a new function scope, which labels, loops and the `for` head of the
+/// enclosing code do not reach into
+pub fn in_function_scope<T>(
+    parser: &mut Parser,
+    is_async: bool,
+    is_generator: bool,
+    parse: impl FnOnce(&mut Parser) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    let labels = std::mem::take(&mut parser.labels);
+    let loop_depth = std::mem::replace(&mut parser.loop_depth, 0);
+    let switch_depth = std::mem::replace(&mut parser.switch_depth, 0);
+    let in_function = std::mem::replace(&mut parser.in_function, true);
+    let in_async = std::mem::replace(&mut parser.in_async, is_async);
+    let in_generator = std::mem::replace(&mut parser.in_generator, is_generator);
+    parser.scopes.enter(true);
+    let result = with_for_init(parser, false, parse);
+    parser.scopes.exit();
+    parser.labels = labels;
+    parser.loop_depth = loop_depth;
+    parser.switch_depth = switch_depth;
+    parser.in_function = in_function;
+    parser.in_async = in_async;
+    parser.in_generator = in_generator;
+    result
+}
+
+/// runs `parse` with `in_for_init` set to `in_for_init`, like `false` inside brackets where
+/// `in` is an operator again even in the head of a `for`
+pub fn with_for_init<T>(
+    parser: &mut Parser,
+    in_for_init: bool,
+    parse: impl FnOnce(&mut Parser) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    let outer = std::mem::replace(&mut parser.in_for_init, in_for_init);
+    let result = parse(parser);
+    parser.in_for_init = outer;
+    result
+}
+
+/// parses `(a, b = 1, ...c)`, declaring the names in the function scope
+fn build_params(parser: &mut Parser) -> Result<Vec<Box<Node>>, ParseError> {
+    expect(parser, Punct::LParen)?;
+    parser.next();
+    let mut params = vec![];
+    while !is_punct(&parser.current, Punct::RParen) {
+        let start = parser.current_span.start;
+        if is_punct(&parser.current, Punct::Ellipsis) {
+            parser.next();
+            let argument = build_binding(parser)?;
+            declare_names(parser, &Token::Var, &argument)?;
+            params.push(Box::new(RestElement {
+                argument,
+                span: parser.span_from(start),
+            }));
+            // the rest parameter comes last
+            break;
+        }
+        let mut param = build_binding(parser)?;
+        declare_names(parser, &Token::Var, &param)?;
+        if is_punct(&parser.current, Punct::Eq) {
+            parser.next();
+            let right = parse_expression(parser, 2)?;
+            param = Box::new(AssignmentPattern {
+                left: param,
+                right,
+                span: parser.span_from(start),
+            });
+        }
+        params.push(param);
+        if !is_punct(&parser.current, Punct::Comma) {
+            break;
+        }
+        parser.next();
+    }
+    expect(parser, Punct::RParen)?;
+    parser.next();
+    Ok(params)
+}
+
+/// parses `{ statements }`, which shares the scope of the parameters
+pub fn build_function_body(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect(parser, Punct::LBrace)?;
+    parser.next();
+    let body = parse_statement_list(parser)?;
+    expect(parser, Punct::RBrace)?;
+    parser.next();
+    ok_box(BlockStatement {
+        body,
+        span: parser.span_from(start),
+    })
+}
+
+pub fn build_return(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect_keyword(parser, Token::Return)?;
+    if !parser.in_function {
+        return Err(ParseError::InvalidJump {
+            reason: "`return` outside of a function",
+            span: parser.current_span,
+        });
+    }
+    parser.next();
+    let mut argument = None;
+    if !is_punct(&parser.current, Punct::Semicolon) && !parser.can_insert_semicolon() {
+        argument = Some(parse_expression(parser, 1)?);
+    }
+    parser.consume_semicolon()?;
+    ok_box(ReturnStatement {
+        argument,
+        span: parser.span_from(start),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::node::Node::*;
+    use crate::node::NumericValue;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    #[test]
+    fn test_function_declaration() {
+        let mut parser = Parser::new("function add(a, b = 1, ...rest) { return a + b }");
+        let (ast, _) = parser.parse().unwrap();
+        let FunctionDeclaration {
+            params, body, span, ..
+        } = &*ast[0]
+        else {
+            panic!("expect function declaration, find {ast:?}");
+        };
+        assert_eq!(*span, Span::new(0, 48));
+        assert!(matches!(*params[0], Identity { .. }));
+        assert!(matches!(*params[1], AssignmentPattern { .. }));
+        assert!(matches!(*params[2], RestElement { .. }));
+        let BlockStatement { body, .. } = &**body else {
+            panic!("expect body, find {body:?}");
+        };
+        assert!(matches!(
+            *body[0],
+            ReturnStatement {
+                argument: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_function_expression() {
+        let mut parser =
+            Parser::new("let f = async function* () {}; (function g() { return\na })()");
+        let (ast, _) = parser.parse().unwrap();
+        let VariableDeclaration { declarations, .. } = &*ast[0] else {
+            panic!("expect declaration, find {ast:?}");
+        };
+        let VariableDeclarator { init, .. } = &*declarations[0] else {
+            panic!("expect declarator, find {declarations:?}");
+        };
+        assert!(matches!(
+            **init,
+            FunctionExpression {
+                id: None,
+                is_async: true,
+                is_generator: true,
+                ..
+            }
+        ));
+        let ExpressionStatement { expression, .. } = &*ast[1] else {
+            panic!("expect expression statement, find {ast:?}");
+        };
+        let CallExpression { callee, .. } = &**expression else {
+            panic!("expect call, find {expression:?}");
+        };
+        let FunctionExpression { body, .. } = &**callee else {
+            panic!("expect function expression, find {callee:?}");
+        };
+        // `return` followed by a line break returns nothing
+        let BlockStatement { body, .. } = &**body else {
+            panic!("expect body, find {body:?}");
+        };
+        assert!(matches!(*body[0], ReturnStatement { argument: None, .. }));
+    }
+
+    #[test]
+    fn test_generator() {
+        let mut parser =
+            Parser::new("function* g() { yield; (yield 1, yield* a); let c = yield\nb }");
+        let (ast, _) = parser.parse().unwrap();
+        let FunctionDeclaration { body, .. } = &*ast[0] else {
+            panic!("expect function declaration, find {ast:?}");
+        };
+        let BlockStatement { body, .. } = &**body else {
+            panic!("expect body, find {body:?}");
+        };
+        let ExpressionStatement { expression, .. } = &*body[0] else {
+            panic!("expect expression statement, find {body:?}");
+        };
+        assert!(matches!(
+            **expression,
+            YieldExpression {
+                argument: None,
+                delegate: false,
+                ..
+            }
+        ));
+        let ExpressionStatement { expression, .. } = &*body[1] else {
+            panic!("expect expression statement, find {body:?}");
+        };
+        let SequenceExpression { expressions, .. } = &**expression else {
+            panic!("expect sequence, find {expression:?}");
+        };
+        assert_eq!(
+            *expressions[0],
+            YieldExpression {
+                argument: Some(Box::new(NumericLiteral {
+                    value: NumericValue::Number(1.0),
+                    raw: "1".to_string(),
+                    span: Span::new(30, 31),
+                })),
+                delegate: false,
+                span: Span::new(24, 31),
+            }
+        );
+        assert!(matches!(
+            *expressions[1],
+            YieldExpression { delegate: true, .. }
+        ));
+        // a line break after `yield` ends it
+        assert!(matches!(*body[3], ExpressionStatement { .. }));
+
+        // outside of a generator `yield` is an identifier, even in one nested in a generator
+        let mut parser = Parser::new("yield; function* g() { function f() { yield } }");
+        let (ast, _) = parser.parse().unwrap();
+        let ExpressionStatement { expression, .. } = &*ast[0] else {
+            panic!("expect expression statement, find {ast:?}");
+        };
+        assert!(matches!(**expression, Identity { .. }));
+
+        // nothing continues a bare `yield` on the next line
+        let mut parser = Parser::new("function* g() { yield\n+ 1 }");
+        let (ast, _) = parser.parse().unwrap();
+        let FunctionDeclaration { body, .. } = &*ast[0] else {
+            panic!("expect function declaration, find {ast:?}");
+        };
+        let BlockStatement { body, .. } = &**body else {
+            panic!("expect body, find {body:?}");
+        };
+        assert_eq!(body.len(), 2);
+        assert!(matches!(*body[1], ExpressionStatement { .. }));
+
+        let error = |input| Parser::new(input).parse().err();
+        assert!(error("function* g() { a + yield b }").is_some());
+        assert!(error("function* g() { yield\n* 2 }").is_some());
+    }
+
+    #[test]
+    fn test_function_errors() {
+        let error = |input| Parser::new(input).parse().err();
+
+        assert_eq!(
+            error("return 1"),
+            Some(ParseError::InvalidJump {
+                reason: "`return` outside of a function",
+                span: Span::new(0, 6),
+            })
+        );
+        assert!(matches!(
+            error("while (a) { function f() { break } }"),
+            Some(ParseError::InvalidJump { .. })
+        ));
+        assert!(matches!(
+            error("function f(a) { let a }"),
+            Some(ParseError::Redeclaration { .. })
+        ));
+        assert!(matches!(
+            error("let f; function f() {}"),
+            Some(ParseError::Redeclaration { .. })
+        ));
+        assert!(error("function (a) {}").is_some());
+        assert!(error("function f(...a, b) {}").is_some());
+        assert_eq!(
+            error("function f(a, a) { var a; { let a } } function f() {}"),
+            None
+        );
+        // in a block a function is declared like `let`, but may still repeat
+        assert_eq!(
+            error("{ function f() {} var f; }"),
+            Some(ParseError::Redeclaration {
+                name: "f".to_string(),
+                span: Span::new(22, 23),
+            })
+        );
+        assert_eq!(
+            error("{ var f; function f() {} }"),
+            Some(ParseError::Redeclaration {
+                name: "f".to_string(),
+                span: Span::new(18, 19),
+            })
+        );
+        assert!(error("{ let f; function f() {} }").is_some());
+        assert_eq!(error("{ function f() {} function f() {} }"), None);
+        assert_eq!(error("var f; function f() {} { function f() {} }"), None);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::error::ParseError;
+use crate::exp::statement_exp::parse_statement;
+use crate::express::{expect_keyword, ok_box, parse_paren_expression};
+use crate::lex::Token;
+use crate::node::Node;
+use crate::node::Node::IfStatement;
+use crate::parser::Parser;
+
+pub fn build_if(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect_keyword(parser, Token::If)?;
+    parser.next();
+    let test = parse_paren_expression(parser)?;
+    let consequent = parse_statement(parser)?;
+    // a dangling `else` belongs to the innermost `if`, which has already taken it
+    let mut alternate = None;
+    if parser.current == Token::Else {
+        parser.next();
+        alternate = Some(parse_statement(parser)?);
+    }
+    ok_box(IfStatement {
+        test,
+        consequent,
+        alternate,
+        span: parser.span_from(start),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_if_else() {
+        let mut parser = Parser::new("if (a) b; else { c }");
+        let (ast, _) = parser.parse().unwrap();
+        let IfStatement { alternate, .. } = &*ast[0] else {
+            panic!("expect if statement, find {ast:?}");
+        };
+        assert!(matches!(alternate.as_deref(), Some(BlockStatement { .. })));
+    }
+
+    #[test]
+    fn test_dangling_else() {
+        let mut parser = Parser::new("if (a) if (b) c; else d");
+        let (ast, _) = parser.parse().unwrap();
+        let IfStatement {
+            consequent,
+            alternate,
+            ..
+        } = &*ast[0]
+        else {
+            panic!("expect if statement, find {ast:?}");
+        };
+        assert!(alternate.is_none());
+        assert!(matches!(
+            **consequent,
+            IfStatement {
+                alternate: Some(_),
+                ..
+            }
+        ));
+    }
+}
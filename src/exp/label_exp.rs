@@ -0,0 +1,196 @@
+use crate::error::ParseError;
+use crate::exp::statement_exp::parse_statement;
+use crate::express::{expect, ok_box};
+use crate::lex::Token;
+use crate::node::Node;
+use crate::node::Node::{BreakStatement, ContinueStatement, Identity, LabeledStatement};
+use crate::parser::{Label, Parser};
+use crate::punct::Punct;
+
+pub fn build_labeled(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let Token::Variable(name) = &parser.current else {
+        return Err(parser.unexpected(&["label"]));
+    };
+    let name = name.to_string();
+    let label = Box::new(Identity {
+        name: name.clone(),
+        span: parser.current_span,
+    });
+    if parser.labels.iter().any(|label| label.name == name) {
+        return Err(ParseError::DuplicateLabel {
+            name,
+            span: parser.current_span,
+        });
+    }
+    parser.next();
+    expect(parser, Punct::Colon)?;
+    parser.next();
+
+    // in `a: b: while ...` both labels name the loop
+    let is_loop = matches!(parser.current, Token::For | Token::While | Token::Do);
+    let statement_start = parser.current_span.start;
+    for outer in parser.labels.iter_mut().rev() {
+        if outer.statement_start != start {
+            break;
+        }
+        outer.statement_start = statement_start;
+        outer.is_loop = is_loop;
+    }
+    parser.labels.push(Label {
+        name,
+        is_loop,
+        statement_start,
+    });
+    let body = parse_statement(parser);
+    parser.labels.pop();
+    ok_box(LabeledStatement {
+        label,
+        body: body?,
+        span: parser.span_from(start),
+    })
+}
+
+pub fn build_break(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    parser.next();
+    let label = parse_jump_label(parser, false)?;
+    if label.is_none() && parser.loop_depth == 0 && parser.switch_depth == 0 {
+        return Err(ParseError::InvalidJump {
+            reason: "`break` outside of a loop or switch",
+            span: parser.span_from(start),
+        });
+    }
+    parser.consume_semicolon()?;
+    ok_box(BreakStatement {
+        label,
+        span: parser.span_from(start),
+    })
+}
+
+pub fn build_continue(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    parser.next();
+    if parser.loop_depth == 0 {
+        return Err(ParseError::InvalidJump {
+            reason: "`continue` outside of a loop",
+            span: parser.span_from(start),
+        });
+    }
+    let label = parse_jump_label(parser, true)?;
+    parser.consume_semicolon()?;
+    ok_box(ContinueStatement {
+        label,
+        span: parser.span_from(start),
+    })
+}
+
+/// the label after `break` or `continue`, which must be on the same line and name an
+/// enclosing statement
+fn parse_jump_label(parser: &mut Parser, continues: bool) -> Result<Option<Box<Node>>, ParseError> {
+    let Token::Variable(name) = &parser.current else {
+        return Ok(None);
+    };
+    if parser.newline_before {
+        return Ok(None);
+    }
+    let span = parser.current_span;
+    let Some(label) = parser.labels.iter().find(|label| label.name == *name) else {
+        return Err(ParseError::UndefinedLabel {
+            name: name.to_string(),
+            span,
+        });
+    };
+    if continues && !label.is_loop {
+        return Err(ParseError::InvalidJump {
+            reason: "`continue` must target the label of a loop",
+            span,
+        });
+    }
+    let name = name.to_string();
+    parser.next();
+    Ok(Some(Box::new(Identity { name, span })))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    #[test]
+    fn test_labeled_loop() {
+        let mut parser =
+            Parser::new("a: b: while (c) { switch (d) { case 1: continue a; } break b }");
+        let (ast, _) = parser.parse().unwrap();
+        let LabeledStatement { body, span, .. } = &*ast[0] else {
+            panic!("expect labeled statement, find {ast:?}");
+        };
+        assert_eq!(*span, Span::new(0, 62));
+        assert!(matches!(**body, LabeledStatement { .. }));
+    }
+
+    #[test]
+    fn test_break_asi() {
+        // the label has to be on the same line as `break`
+        let mut parser = Parser::new("a: while (b) { break\na }");
+        let (ast, _) = parser.parse().unwrap();
+        let LabeledStatement { body, .. } = &*ast[0] else {
+            panic!("expect labeled statement, find {ast:?}");
+        };
+        let WhileStatement { body, .. } = &**body else {
+            panic!("expect while statement, find {body:?}");
+        };
+        assert!(matches!(
+            &**body,
+            BlockStatement { body, .. } if matches!(*body[0], BreakStatement { label: None, .. })
+        ));
+    }
+
+    #[test]
+    fn test_jump_errors() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert_eq!(
+            error("break;"),
+            ParseError::InvalidJump {
+                reason: "`break` outside of a loop or switch",
+                span: Span::new(0, 5),
+            }
+        );
+        assert_eq!(
+            error("switch (a) { default: continue }"),
+            ParseError::InvalidJump {
+                reason: "`continue` outside of a loop",
+                span: Span::new(22, 30),
+            }
+        );
+        assert_eq!(
+            error("a: { while (b) continue a }"),
+            ParseError::InvalidJump {
+                reason: "`continue` must target the label of a loop",
+                span: Span::new(24, 25),
+            }
+        );
+        assert_eq!(
+            error("while (a) break b"),
+            ParseError::UndefinedLabel {
+                name: "b".to_string(),
+                span: Span::new(16, 17),
+            }
+        );
+        assert_eq!(
+            error("a: { a: ; }"),
+            ParseError::DuplicateLabel {
+                name: "a".to_string(),
+                span: Span::new(5, 6),
+            }
+        );
+        // labels do not leak out of the statement they label
+        assert!(matches!(
+            error("a: ; while (b) break a"),
+            ParseError::UndefinedLabel { .. }
+        ));
+    }
+}
@@ -1,2 +1,11 @@
-pub mod for_exp;
+pub mod array_exp;
+pub mod arrow_exp;
 pub mod declaration_exp;
+pub mod for_exp;
+pub mod function_exp;
+pub mod if_exp;
+pub mod label_exp;
+pub mod object_exp;
+pub mod statement_exp;
+pub mod switch_exp;
+pub mod while_exp;
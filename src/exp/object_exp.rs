@@ -0,0 +1,293 @@
+use crate::error::ParseError;
+use crate::exp::function_exp::{build_method, with_for_init};
+use crate::express::{expect, is_punct, ok_box, parse_expression};
+use crate::lex::Token;
+use crate::node::Node::{
+    Identity, NumericLiteral, ObjectExpression, Property, SpreadElement, StringLiteral,
+};
+use crate::node::{Node, NumericValue};
+use crate::parser::Parser;
+use crate::punct::Punct;
+
+pub fn build_object(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect(parser, Punct::LBrace)?;
+    parser.next();
+    // `in` is an operator again inside the braces
+    let properties = with_for_init(parser, false, |parser| {
+        let mut properties = vec![];
+        while !is_punct(&parser.current, Punct::RBrace) {
+            properties.push(build_property(parser)?);
+            if !is_punct(&parser.current, Punct::Comma) {
+                break;
+            }
+            parser.next();
+        }
+        Ok(properties)
+    })?;
+    expect(parser, Punct::RBrace)?;
+    parser.next();
+    ok_box(ObjectExpression {
+        properties,
+        span: parser.span_from(start),
+    })
+}
+
+fn build_property(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    if is_punct(&parser.current, Punct::Ellipsis) {
+        parser.next();
+        let argument = parse_expression(parser, 2)?;
+        return ok_box(SpreadElement {
+            argument,
+            span: parser.span_from(start),
+        });
+    }
+
+    // `async`, `get` and `set` are modifiers only when a key follows them
+    let is_async = parser.current == Token::Async && at_modifier(parser);
+    if is_async {
+        parser.next();
+    }
+    let is_generator = is_punct(&parser.current, Punct::Star);
+    if is_generator {
+        parser.next();
+    }
+    let mut kind = "init";
+    if !is_async && !is_generator {
+        let accessor = match &parser.current {
+            Token::Variable(name) if name == "get" => Some("get"),
+            Token::Variable(name) if name == "set" => Some("set"),
+            _ => None,
+        };
+        if let Some(accessor) = accessor
+            && at_modifier(parser)
+        {
+            kind = accessor;
+            parser.next();
+        }
+    }
+
+    let may_be_shorthand = matches!(
+        parser.current,
+        Token::Variable(_) | Token::Async | Token::Let | Token::Await | Token::Yield
+    );
+    let (key, computed) = build_property_key(parser)?;
+
+    let (value, shorthand, method) = if is_punct(&parser.current, Punct::LParen) {
+        (
+            build_method(parser, is_async, is_generator)?,
+            false,
+            kind == "init",
+        )
+    } else if kind != "init" || is_async || is_generator {
+        return Err(parser.unexpected(&["`(`"]));
+    } else if is_punct(&parser.current, Punct::Colon) {
+        parser.next();
+        (parse_expression(parser, 2)?, false, false)
+    } else if may_be_shorthand && !computed {
+        let Identity { name, span } = &*key else {
+            unreachable!("an identifier key is an `Identity`");
+        };
+        let value = Box::new(Identity {
+            name: name.clone(),
+            span: *span,
+        });
+        (value, true, false)
+    } else {
+        return Err(parser.unexpected(&["`:`"]));
+    };
+    ok_box(Property {
+        key,
+        value,
+        kind: kind.to_string(),
+        computed,
+        shorthand,
+        method,
+        span: parser.span_from(start),
+    })
+}
+
+/// whether the current word modifies the key after it, as in `get a() {}`, rather than
+/// being the key itself, as in `get() {}` or `{ get }`
+fn at_modifier(parser: &mut Parser) -> bool {
+    !matches!(
+        parser.peek(),
+        Token::Punct(Punct::Comma | Punct::Colon | Punct::LParen | Punct::RBrace | Punct::Eq)
+    ) && (parser.current != Token::Async || !parser.peek_newline_before())
+}
+
+/// parses a property key, returning it and whether it is computed, like `[k]`
+fn build_property_key(parser: &mut Parser) -> Result<(Box<Node>, bool), ParseError> {
+    let span = parser.current_span;
+    let key = match &parser.current {
+        Token::Punct(Punct::LBracket) => {
+            parser.next();
+            let key = parse_expression(parser, 2)?;
+            expect(parser, Punct::RBracket)?;
+            parser.next();
+            return Ok((key, true));
+        }
+        Token::String(value) => Box::new(StringLiteral {
+            value: value.to_string(),
+            span,
+        }),
+        Token::Digit(raw) => Box::new(NumericLiteral {
+            value: NumericValue::parse(raw),
+            raw: raw.to_string(),
+            span,
+        }),
+        token => match token.identifier_name() {
+            Some(name) => Box::new(Identity { name, span }),
+            None => return Err(parser.unexpected(&["property name"])),
+        },
+    };
+    parser.next();
+    Ok((key, false))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::ParseError;
+    use crate::express_test::test::expressions;
+    use crate::node::Node;
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    /// the kind, computed, shorthand and method flags of every property
+    fn flags(node: &Node) -> Vec<(String, bool, bool, bool)> {
+        let ObjectExpression { properties, .. } = node else {
+            panic!("expect object, find {node:?}");
+        };
+        properties
+            .iter()
+            .map(|property| match &**property {
+                Property {
+                    kind,
+                    computed,
+                    shorthand,
+                    method,
+                    ..
+                } => (kind.clone(), *computed, *shorthand, *method),
+                SpreadElement { .. } => ("spread".to_string(), false, false, false),
+                _ => panic!("expect property, find {property:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_object() {
+        let mut parser = Parser::new("({ a: 1, b, [c]: 2, 'd': 3, 4: e, if: f, ...g, })");
+        let nodes = expressions(&mut parser);
+        let init = |computed, shorthand| ("init".to_string(), computed, shorthand, false);
+        assert_eq!(
+            flags(&nodes[0]),
+            vec![
+                init(false, false),
+                init(false, true),
+                init(true, false),
+                init(false, false),
+                init(false, false),
+                init(false, false),
+                ("spread".to_string(), false, false, false),
+            ]
+        );
+        assert_eq!(nodes[0].span(), Span::new(1, 48));
+    }
+
+    #[test]
+    fn test_methods() {
+        let mut parser = Parser::new(
+            "({ a() {}, get b() { return 1 }, set b(v) {}, async c() {}, *d() { yield x }, \
+             async *e() {}, get() {}, set: 1, async, get })",
+        );
+        let nodes = expressions(&mut parser);
+        let flag = |kind: &str, shorthand, method| (kind.to_string(), false, shorthand, method);
+        assert_eq!(
+            flags(&nodes[0]),
+            vec![
+                flag("init", false, true),
+                flag("get", false, false),
+                flag("set", false, false),
+                flag("init", false, true),
+                flag("init", false, true),
+                flag("init", false, true),
+                flag("init", false, true),
+                flag("init", false, false),
+                flag("init", true, false),
+                flag("init", true, false),
+            ]
+        );
+        let ObjectExpression { properties, .. } = &*nodes[0] else {
+            unreachable!();
+        };
+        let Property { value, .. } = &*properties[5] else {
+            unreachable!();
+        };
+        assert!(matches!(
+            **value,
+            FunctionExpression {
+                is_async: true,
+                is_generator: true,
+                ..
+            }
+        ));
+        // a generator method may `yield` in its body
+        let Property { value, .. } = &*properties[4] else {
+            unreachable!();
+        };
+        let FunctionExpression { body, .. } = &**value else {
+            panic!("expect method, find {value:?}");
+        };
+        let BlockStatement { body, .. } = &**body else {
+            panic!("expect body, find {body:?}");
+        };
+        let ExpressionStatement { expression, .. } = &*body[0] else {
+            panic!("expect expression statement, find {body:?}");
+        };
+        assert!(matches!(**expression, YieldExpression { .. }));
+    }
+
+    #[test]
+    fn test_block_or_object() {
+        // at the start of a statement `{` opens a block
+        let mut parser = Parser::new("{ a: 1 }");
+        let (ast, _) = parser.parse().unwrap();
+        assert!(matches!(*ast[0], BlockStatement { .. }));
+
+        let mut parser = Parser::new("a = { b: 1 }.b; () => ({}); () => {}");
+        let nodes = expressions(&mut parser);
+        assert!(matches!(*nodes[0], AssignmentExpression { .. }));
+        let ArrowFunctionExpression { body, .. } = &*nodes[1] else {
+            panic!("expect arrow function, find {:?}", nodes[1]);
+        };
+        assert!(matches!(**body, ObjectExpression { .. }));
+        let ArrowFunctionExpression { body, .. } = &*nodes[2] else {
+            panic!("expect arrow function, find {:?}", nodes[2]);
+        };
+        assert!(matches!(**body, BlockStatement { .. }));
+    }
+
+    #[test]
+    fn test_object_errors() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert!(matches!(
+            error("({ if })"),
+            ParseError::UnexpectedToken { .. }
+        ));
+        assert!(matches!(
+            error("({ get a: 1 })"),
+            ParseError::UnexpectedToken { .. }
+        ));
+        assert!(matches!(
+            error("({ a b })"),
+            ParseError::UnexpectedToken { .. }
+        ));
+        assert!(matches!(
+            error("({ 'a' })"),
+            ParseError::UnexpectedToken { .. }
+        ));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::error::ParseError;
+use crate::exp::declaration_exp::build_let;
+use crate::exp::for_exp::build_for;
+use crate::exp::function_exp::{build_function, build_return};
+use crate::exp::if_exp::build_if;
+use crate::exp::label_exp::{build_break, build_continue, build_labeled};
+use crate::exp::switch_exp::build_switch;
+use crate::exp::while_exp::{build_do_while, build_while};
+use crate::express::{expect, ok_box, parse_expression};
+use crate::lex::Token;
+use crate::node::Node;
+use crate::node::Node::{BlockStatement, EmptyStatement, ExpressionStatement};
+use crate::parser::Parser;
+use crate::punct::Punct;
+
+pub fn parse_statement(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    parser.nested(build_statement)
+}
+
+fn build_statement(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    if parser.current == Token::Let && !parser.at_let_declaration() {
+        return build_expression_statement(parser);
+    }
+    if parser.current == Token::Async
+        && *parser.peek() == Token::Function
+        && !parser.peek_newline_before()
+    {
+        return build_function(parser, true);
+    }
+    if let Token::Variable(_) = parser.current
+        && *parser.peek() == Token::Punct(Punct::Colon)
+    {
+        return build_labeled(parser);
+    }
+    match parser.current {
+        Token::Punct(Punct::LBrace) => build_block(parser),
+        Token::Punct(Punct::Semicolon) => {
+            parser.next();
+            ok_box(EmptyStatement {
+                span: parser.span_from(start),
+            })
+        }
+        Token::Var | Token::Let | Token::Const => {
+            let declaration = build_let(parser)?;
+            parser.consume_semicolon()?;
+            Ok(declaration)
+        }
+        Token::For => build_for(parser),
+        Token::If => build_if(parser),
+        Token::While => build_while(parser),
+        Token::Do => build_do_while(parser),
+        Token::Switch => build_switch(parser),
+        Token::Break => build_break(parser),
+        Token::Continue => build_continue(parser),
+        Token::Function => build_function(parser, true),
+        Token::Return => build_return(parser),
+        _ => build_expression_statement(parser),
+    }
+}
+
+/// parses statements up to a closing `}` or the end of input, leaving it unconsumed
+pub fn parse_statement_list(parser: &mut Parser) -> Result<Vec<Box<Node>>, ParseError> {
+    let mut body = vec![];
+    while !matches!(parser.current, Token::Punct(Punct::RBrace) | Token::EOF) {
+        body.push(parse_statement(parser)?);
+    }
+    Ok(body)
+}
+
+/// parses the body of a loop, where `break` and `continue` are allowed
+pub fn parse_loop_body(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    parser.loop_depth += 1;
+    let body = parse_statement(parser);
+    parser.loop_depth -= 1;
+    body
+}
+
+pub fn build_block(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect(parser, Punct::LBrace)?;
+    parser.next();
+    parser.scopes.enter(false);
+    let body = parse_statement_list(parser)?;
+    parser.scopes.exit();
+    expect(parser, Punct::RBrace)?;
+    parser.next();
+    ok_box(BlockStatement {
+        body,
+        span: parser.span_from(start),
+    })
+}
+
+fn build_expression_statement(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let expression = parse_expression(parser, 1)?;
+    parser.consume_semicolon()?;
+    ok_box(ExpressionStatement {
+        expression,
+        span: parser.span_from(start),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    #[test]
+    fn test_block() {
+        let mut parser = Parser::new("{ a; { } ; }");
+        let (ast, _) = parser.parse().unwrap();
+        assert_eq!(
+            ast,
+            vec![Box::new(BlockStatement {
+                body: vec![
+                    Box::new(ExpressionStatement {
+                        expression: Box::new(Identity {
+                            name: "a".to_string(),
+                            span: Span::new(2, 3),
+                        }),
+                        span: Span::new(2, 4),
+                    }),
+                    Box::new(BlockStatement {
+                        body: vec![],
+                        span: Span::new(5, 8),
+                    }),
+                    Box::new(EmptyStatement {
+                        span: Span::new(9, 10),
+                    }),
+                ],
+                span: Span::new(0, 12),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_unclosed_block() {
+        let mut parser = Parser::new("{ a; ");
+        assert!(parser.parse().is_err());
+        let mut parser = Parser::new("a; }");
+        assert!(parser.parse().is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use crate::error::ParseError;
+use crate::exp::statement_exp::parse_statement;
+use crate::express::{expect, expect_keyword, ok_box, parse_expression, parse_paren_expression};
+use crate::lex::Token;
+use crate::node::Node;
+use crate::node::Node::{SwitchCase, SwitchStatement};
+use crate::parser::Parser;
+use crate::punct::Punct;
+
+pub fn build_switch(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect_keyword(parser, Token::Switch)?;
+    parser.next();
+    let discriminant = parse_paren_expression(parser)?;
+    expect(parser, Punct::LBrace)?;
+    parser.next();
+    parser.switch_depth += 1;
+    // all the cases share one scope
+    parser.scopes.enter(false);
+    let mut cases = vec![];
+    let mut has_default = false;
+    while parser.current != Token::Punct(Punct::RBrace) {
+        let case_start = parser.current_span.start;
+        let test = match parser.current {
+            Token::Case => {
+                parser.next();
+                Some(parse_expression(parser, 1)?)
+            }
+            Token::Default if !has_default => {
+                has_default = true;
+                parser.next();
+                None
+            }
+            // a second `default` is reported as not being a `case`
+            _ => return Err(parser.unexpected(&["`case`", "`}`"])),
+        };
+        expect(parser, Punct::Colon)?;
+        parser.next();
+        let mut consequent = vec![];
+        while !matches!(
+            parser.current,
+            Token::Case | Token::Default | Token::Punct(Punct::RBrace) | Token::EOF
+        ) {
+            consequent.push(parse_statement(parser)?);
+        }
+        cases.push(Box::new(SwitchCase {
+            test,
+            consequent,
+            span: parser.span_from(case_start),
+        }));
+    }
+    parser.scopes.exit();
+    parser.switch_depth -= 1;
+    parser.next();
+    ok_box(SwitchStatement {
+        discriminant,
+        cases,
+        span: parser.span_from(start),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::lex::Token;
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    #[test]
+    fn test_switch() {
+        let mut parser = Parser::new("switch (a) { case 1: b; break; default: case 2: }");
+        let (ast, _) = parser.parse().unwrap();
+        let SwitchStatement { cases, span, .. } = &*ast[0] else {
+            panic!("expect switch statement, find {ast:?}");
+        };
+        assert_eq!(*span, Span::new(0, 49));
+        let tests = cases
+            .iter()
+            .map(|case| match &**case {
+                SwitchCase {
+                    test, consequent, ..
+                } => (test.is_some(), consequent.len()),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(tests, vec![(true, 2), (false, 0), (true, 0)]);
+    }
+
+    #[test]
+    fn test_duplicate_default() {
+        let mut parser = Parser::new("switch (a) { default: default: }");
+        let error = parser.parse().unwrap_err();
+        assert_eq!(error.span(), Span::new(22, 29));
+        let mut parser = Parser::new("switch (a) { b }");
+        assert!(parser.parse().is_err());
+        assert_eq!(parser.current, Token::Variable("b".into()));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::error::ParseError;
+use crate::exp::statement_exp::parse_loop_body;
+use crate::express::{expect_keyword, is_punct, ok_box, parse_paren_expression};
+use crate::lex::Token;
+use crate::node::Node;
+use crate::node::Node::{DoWhileStatement, WhileStatement};
+use crate::parser::Parser;
+use crate::punct::Punct;
+
+pub fn build_while(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect_keyword(parser, Token::While)?;
+    parser.next();
+    let test = parse_paren_expression(parser)?;
+    let body = parse_loop_body(parser)?;
+    ok_box(WhileStatement {
+        test,
+        body,
+        span: parser.span_from(start),
+    })
+}
+
+pub fn build_do_while(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    expect_keyword(parser, Token::Do)?;
+    parser.next();
+    let body = parse_loop_body(parser)?;
+    expect_keyword(parser, Token::While)?;
+    parser.next();
+    let test = parse_paren_expression(parser)?;
+    // a `;` is inserted after `do ... while (...)` even without a line break
+    if is_punct(&parser.current, Punct::Semicolon) {
+        parser.next();
+    }
+    ok_box(DoWhileStatement {
+        body,
+        test,
+        span: parser.span_from(start),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::node::Node::*;
+    use crate::parser::Parser;
+    use crate::span::Span;
+
+    #[test]
+    fn test_while() {
+        let mut parser = Parser::new("while (a) { a-- }");
+        let (ast, _) = parser.parse().unwrap();
+        assert!(matches!(*ast[0], WhileStatement { .. }));
+        assert_eq!(ast[0].span(), Span::new(0, 17));
+    }
+
+    #[test]
+    fn test_do_while() {
+        let mut parser = Parser::new("do a++; while (a < 3) b()");
+        let (ast, _) = parser.parse().unwrap();
+        assert_eq!(ast.len(), 2);
+        assert!(matches!(*ast[0], DoWhileStatement { .. }));
+        assert_eq!(ast[0].span(), Span::new(0, 21));
+    }
+}
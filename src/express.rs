@@ -1,246 +1,783 @@
+use crate::error::ParseError;
+use crate::exp::array_exp::{build_array, to_pattern};
+use crate::exp::arrow_exp::{at_arrow, build_arrow, parse_cover_paren};
+use crate::exp::function_exp::{build_function, with_for_init};
+use crate::exp::object_exp::build_object;
 use crate::lex::Token;
-use crate::node::Node;
+use crate::node::{Node, NumericValue};
 use crate::parser::Parser;
+use crate::punct::Punct;
+use crate::span::Span;
 
-pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>, String> {
-    let word = parser.current.clone();
-    if let Token::Control(s) = word {
-        let l = get_level(&parser.current)?;
-        return match s.as_str() {
-            "++" => {
-                parser.next();
-                Ok(Box::new(Node::UpdateExpression {
-                    operator: s.to_string(),
-                    prefix: true,
-                    argument: parse_expression(parser, l + 1)?,
-                }))
-            }
-            "+" | "-" | "!" | "typeof" => {
-                parser.next();
-                Ok(Box::new(Node::UnaryExpression {
-                    operator: s.to_string(),
-                    prefix: true,
-                    argument: parse_expression(parser, l + 1)?,
-                }))
-            }
-            "(" => {
-                parser.next();
-                let express = parse_expression(parser, 1)?;
-                if !is_ctrl_word(&parser.current, ")") {
-                    return Err("expect )".to_string());
-                }
-                parser.next();
-                Ok(express)
-            }
-            _ => Err("expect control,".to_string()),
-        };
+pub fn parse_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>, ParseError> {
+    parser.nested(|parser| build_expression(parser, min_level))
+}
+
+fn build_expression(parser: &mut Parser, min_level: u8) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let expression = match build_operand(parser, min_level)? {
+        Operand::Open(left) => build_operators(parser, left, start, min_level)?,
+        Operand::Closed(node) => node,
+    };
+    if min_level <= 1 && is_punct(&parser.current, Punct::Comma) {
+        return build_sequence(parser, expression, start);
     }
-    let mut left: Result<Box<Node>, String>;
+    Ok(expression)
+}
 
-    if let Token::Variable(s) = word {
-        left = ok_box(Node::Identity {
-            name: s.to_string(),
-        })
-    } else if let Token::Digit(d) = word {
-        left = ok_box(Node::NumericLiteral {
-            value: d.to_string(),
-        })
+/// parses the rest of `a, b, c` after its first expression
+fn build_sequence(
+    parser: &mut Parser,
+    first: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    let mut expressions = vec![first];
+    while is_punct(&parser.current, Punct::Comma) {
+        parser.next();
+        expressions.push(parse_expression(parser, 2)?);
+    }
+    ok_box(Node::SequenceExpression {
+        expressions,
+        span: parser.span_from(start),
+    })
+}
+
+/// the start of an expression
+enum Operand {
+    /// may be followed by operators, like `a` in `a + b`
+    Open(Box<Node>),
+    /// already took every operator that may follow, like the body of `x => x + 1`
+    Closed(Box<Node>),
+}
+
+// `build_operand` and `build_operators` only dispatch, and every case gets its own
+// function, so that the frames of the recursion through them stay small
+fn build_operand(parser: &mut Parser, min_level: u8) -> Result<Operand, ParseError> {
+    if let Token::Punct(Punct::Slash | Punct::SlashEq) = parser.current {
+        parser.rescan_regexp();
+    }
+    let left = match parser.current {
+        Token::Typeof | Token::Void | Token::Delete => build_unary(parser),
+        Token::Punct(Punct::Plus | Punct::Minus | Punct::Bang | Punct::Tilde) => {
+            build_unary(parser)
+        }
+        Token::Punct(Punct::PlusPlus | Punct::MinusMinus) => build_prefix_update(parser),
+        Token::Await if parser.in_async => build_await(parser),
+        Token::Yield if parser.in_generator => {
+            return build_yield(parser, min_level).map(Operand::Closed);
+        }
+        Token::Punct(Punct::LParen) => return build_paren(parser, min_level),
+        Token::Punct(Punct::LBracket) => build_array(parser),
+        Token::Punct(Punct::LBrace) => build_object(parser),
+        Token::Template { .. } => parse_template(parser, false),
+        Token::Variable(_) => return build_identifier(parser, min_level),
+        Token::Async => return build_async(parser, min_level),
+        Token::Function => build_function(parser, false),
+        // contextual keywords are plain identifiers in sloppy mode code
+        Token::Let | Token::Await | Token::Yield => return build_identifier(parser, min_level),
+        _ => build_literal(parser),
+    };
+    left.map(Operand::Open)
+}
+
+/// parses `-a`, `!a`, `typeof a` and the like
+fn build_unary(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let operator = parser.current.to_string().to_lowercase();
+    parser.next();
+    let argument = parse_expression(parser, 16)?;
+    ok_box(Node::UnaryExpression {
+        operator,
+        prefix: true,
+        argument,
+        span: parser.span_from(start),
+    })
+}
+
+/// parses `++a` or `--a`
+fn build_prefix_update(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let operator = parser.current.to_string();
+    parser.next();
+    let argument = simple_target(parse_expression(parser, 18)?)?;
+    ok_box(Node::UpdateExpression {
+        operator,
+        prefix: true,
+        argument,
+        span: parser.span_from(start),
+    })
+}
+
+fn build_await(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    parser.next();
+    let argument = parse_expression(parser, 16)?;
+    ok_box(Node::AwaitExpression {
+        argument,
+        span: parser.span_from(start),
+    })
+}
+
+/// parses `yield`, `yield a` or `yield* a` in a generator
+fn build_yield(parser: &mut Parser, min_level: u8) -> Result<Box<Node>, ParseError> {
+    // like an assignment, `yield` is no operand of a tighter operator
+    if min_level > 2 {
+        return Err(parser.unexpected(&["expression"]));
+    }
+    let start = parser.current_span.start;
+    parser.next();
+    let delegate = is_punct(&parser.current, Punct::Star) && !parser.newline_before;
+    if delegate {
+        parser.next();
+    }
+    // a bare `yield` ends at a line break or at whatever closes the expression
+    let bare = !delegate
+        && (parser.newline_before
+            || matches!(
+                parser.current,
+                Token::EOF
+                    | Token::Punct(
+                        Punct::Semicolon
+                            | Punct::Colon
+                            | Punct::Comma
+                            | Punct::RParen
+                            | Punct::RBracket
+                            | Punct::RBrace
+                    )
+            ));
+    let argument = if bare {
+        None
     } else {
-        return Err(format!("unsupported parse_express start {word}"));
+        Some(parse_expression(parser, 2)?)
+    };
+    ok_box(Node::YieldExpression {
+        argument,
+        delegate,
+        span: parser.span_from(start),
+    })
+}
+
+/// parses `(a, b)`, which is an arrow function's parameters when `=>` follows
+fn build_paren(parser: &mut Parser, min_level: u8) -> Result<Operand, ParseError> {
+    let start = parser.current_span.start;
+    let cover = parse_cover_paren(parser)?;
+    if at_arrow(parser) && min_level <= 2 {
+        return build_arrow(parser, start, cover.into_params()?, false).map(Operand::Closed);
     }
+    cover.into_expression().map(Operand::Open)
+}
 
+/// parses a name, which is an arrow function's parameter when `=>` follows; `let`, `async`,
+/// `await` and `yield` are names wherever they are not keywords
+fn build_identifier(parser: &mut Parser, min_level: u8) -> Result<Operand, ParseError> {
+    let name = match &parser.current {
+        Token::Variable(s) => s.to_string(),
+        word @ (Token::Let | Token::Async | Token::Await | Token::Yield) => {
+            word.to_string().to_lowercase()
+        }
+        _ => return Err(parser.unexpected(&["identifier"])),
+    };
+    let start = parser.current_span.start;
+    let id = Box::new(Node::Identity {
+        name,
+        span: parser.current_span,
+    });
     parser.next();
+    if at_arrow(parser) && min_level <= 2 {
+        return build_arrow(parser, start, vec![id], false).map(Operand::Closed);
+    }
+    Ok(Operand::Open(id))
+}
+
+/// parses what starts with `async`: an async arrow function or function expression, a call
+/// of a function named `async`, or just that name
+fn build_async(parser: &mut Parser, min_level: u8) -> Result<Operand, ParseError> {
+    if parser.peek_newline_before() {
+        return build_identifier(parser, min_level);
+    }
+    let start = parser.current_span.start;
+    let span = parser.current_span;
+    match parser.peek() {
+        Token::Function => return build_function(parser, false).map(Operand::Open),
+        // like any arrow function, `async x => ...` is no operand of a tighter operator
+        Token::Variable(_) if min_level <= 2 => {}
+        Token::Punct(Punct::LParen) => {}
+        _ => return build_identifier(parser, min_level),
+    }
+    parser.next();
+    if let Token::Variable(s) = &parser.current {
+        // `async x => ...`
+        let id = Box::new(Node::Identity {
+            name: s.to_string(),
+            span: parser.current_span,
+        });
+        parser.next();
+        return build_arrow(parser, start, vec![id], true).map(Operand::Closed);
+    }
+    let cover = parse_cover_paren(parser)?;
+    if at_arrow(parser) && min_level <= 2 {
+        return build_arrow(parser, start, cover.into_params()?, true).map(Operand::Closed);
+    }
+    // otherwise `async` is called like any function
+    ok_box(Node::CallExpression {
+        callee: Box::new(Node::Identity {
+            name: "async".to_string(),
+            span,
+        }),
+        arguments: cover.into_arguments(),
+        optional: false,
+        span: parser.span_from(start),
+    })
+    .map(Operand::Open)
+}
+
+fn build_literal(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let span = parser.current_span;
+    let literal = match parser.current.clone() {
+        Token::True | Token::False => Node::BooleanLiteral {
+            value: parser.current == Token::True,
+            span,
+        },
+        Token::Null => Node::NullLiteral { span },
+        Token::This => Node::ThisExpression { span },
+        Token::Digit(d) => Node::NumericLiteral {
+            value: NumericValue::parse(&d),
+            raw: d.into_owned(),
+            span,
+        },
+        Token::String(s) => Node::StringLiteral {
+            value: s.into_owned(),
+            span,
+        },
+        Token::RegExp { pattern, flags } => Node::RegExpLiteral {
+            pattern: pattern.into_owned(),
+            flags: flags.into_owned(),
+            span,
+        },
+        _ => return Err(parser.unexpected(&["expression"])),
+    };
+    parser.next();
+    ok_box(literal)
+}
+
+/// applies the operators that follow `left` and bind at least as tightly as `min_level`
+fn build_operators(
+    parser: &mut Parser,
+    mut left: Box<Node>,
+    start: usize,
+    min_level: u8,
+) -> Result<Box<Node>, ParseError> {
+    // nothing binds tighter than a postfix update, so `a++.b` and `a++ ++` stop after it
+    let mut postfix = false;
+    // whether `left` holds an optional chain, wrapped into a `ChainExpression` once it ends
+    let mut chain = false;
     loop {
         let operator = parser.current.clone();
+        if chain
+            && !matches!(
+                operator,
+                Token::Punct(Punct::Dot | Punct::LBracket | Punct::LParen | Punct::QuestionDot)
+            )
+        {
+            if let Token::Template { .. } = operator {
+                // `a?.b\`c\`` is not allowed
+                return Err(parser.unexpected(&["`;`"]));
+            }
+            left = close_chain(parser, left, start)?;
+            chain = false;
+        }
         match &operator {
-            Token::Control(s) => match s.as_str() {
-                ";" | ":" | ")" | "]" | "," => break,
+            Token::Punct(p) => match p {
+                Punct::Semicolon
+                | Punct::Colon
+                | Punct::RParen
+                | Punct::RBracket
+                | Punct::RBrace
+                | Punct::Comma => break,
                 _ => {}
             },
+            Token::In | Token::Variable(_) if parser.in_for_init => break,
+            Token::Template { .. } | Token::In | Token::Instanceof => {}
             Token::EOF => break,
-            Token::Variable(_) => return Err("syntax error:".to_string()),
-            Token::Digit(_) => return Err("syntax error:".to_string()),
+            // on a new line, the token starts the next statement
+            _ if parser.newline_before => break,
+            Token::Variable(_) | Token::Digit(_) | Token::String(_) | Token::RegExp { .. } => {
+                return Err(parser.unexpected(&["operator"]));
+            }
             _ => break,
         }
-        let l = get_level(&parser.current)?;
-        if l < min_level {
+        let l = get_level(&parser.current).ok_or_else(|| parser.unexpected(&["operator"]))?;
+        if l < min_level || (postfix && l >= 17) {
             break;
         }
 
-        match &operator {
-            Token::Control(s) => match s.as_str() {
-                "++" | "--" => {
-                    parser.next();
-                    return ok_box(Node::UpdateExpression {
-                        operator: s.to_string(),
-                        prefix: false,
-                        argument: left?,
-                    });
-                }
-                "?" => {
-                    parser.next();
-                    let consequent = parse_expression(parser, l)?;
-                    if !is_ctrl_word(&parser.current, ":") {
-                        return Err("expect :".to_string());
-                    }
-                    parser.next();
-                    let alternate = parse_expression(parser, l + 1)?;
-                    return ok_box(Node::ConditionalExpression {
-                        test: left?,
-                        consequent,
-                        alternate,
-                    });
-                }
-                "(" => {
-                    parser.next();
-                    let mut arguments: Vec<Box<Node>> = vec![];
-                    loop {
-                        let next = &parser.current;
-                        if is_ctrl_word(&next, ")") {
-                            break;
-                        }
-                        let express = parse_expression(parser, 1)?;
-                        arguments.push(express);
-                        let current = &parser.current.clone();
-                        if is_ctrl_word(&current, ",") {
-                            parser.next();
-                        }
-                        if is_ctrl_word(&current, ")") {
-                            parser.next();
-                            break;
-                        }
-                    }
-                    return ok_box(Node::CallExpression {
-                        callee: left?,
-                        arguments,
-                    });
+        let next = match &operator {
+            Token::Template { .. } => build_tagged_template(parser, left, start),
+            Token::In | Token::Instanceof => build_binary(parser, left, start, l + 1),
+            Token::Punct(p) => match *p {
+                // restricted production: `a\n++b` is `a; ++b`
+                Punct::PlusPlus | Punct::MinusMinus if parser.newline_before => break,
+                Punct::PlusPlus | Punct::MinusMinus => {
+                    postfix = true;
+                    build_postfix_update(parser, left, start)
                 }
-                "=" => {
-                    parser.next();
-                    let right = parse_expression(parser, l + 1)?;
-                    left = ok_box(Node::AssignmentExpression {
-                        operator: s.to_string(),
-                        left: left?,
-                        right,
-                    })
+                Punct::Question => return build_conditional(parser, left, start),
+                Punct::LParen => build_call(parser, left, start),
+                p if p.is_assignment() => build_assignment(parser, left, start, l),
+                Punct::Dot | Punct::LBracket => build_member(parser, left, start),
+                Punct::QuestionDot => {
+                    chain = true;
+                    build_optional(parser, left, start)
                 }
-                "." => {
-                    parser.next();
-                    let right = parse_expression(parser, l + 1)?;
-                    left = ok_box(Node::MemberExpression {
-                        object: left?,
-                        property: right,
-                    })
+                Punct::AmpAmp | Punct::PipePipe | Punct::QuestionQuestion => {
+                    build_logical(parser, left, start, l)
                 }
-                "+" | "-" | "*" | "/" | "%" | ">" | "<" | ">=" | "<=" => {
-                    parser.next();
-                    let right = parse_expression(parser, l + 1)?;
-                    left = ok_box(Node::BinaryExpression {
-                        operator: s.to_string(),
-                        left: left?,
-                        right,
-                    })
+                Punct::StarStar => {
+                    check_exponent_base(&left, start)?;
+                    // `**` is right-associative
+                    build_binary(parser, left, start, l)
                 }
+                Punct::Plus
+                | Punct::Minus
+                | Punct::Star
+                | Punct::Slash
+                | Punct::Percent
+                | Punct::Lt
+                | Punct::Gt
+                | Punct::LtEq
+                | Punct::GtEq
+                | Punct::EqEq
+                | Punct::NotEq
+                | Punct::EqEqEq
+                | Punct::NotEqEq
+                | Punct::Shl
+                | Punct::Shr
+                | Punct::UShr
+                | Punct::Amp
+                | Punct::Pipe
+                | Punct::Caret => build_binary(parser, left, start, l + 1),
                 _ => {
-                    return Err(format!("unsupported operator {:?}", &operator));
+                    return Err(parser.unexpected(&["operator"]));
                 }
             },
             _ => {
                 break;
             }
-        }
+        };
+        left = next?;
     }
-    left
+    if chain {
+        left = close_chain(parser, left, start)?;
+    }
+    Ok(left)
 }
 
-pub fn ok_box(node: Node) -> Result<Box<Node>, String> {
-    Ok(Box::new(node))
+fn build_tagged_template(
+    parser: &mut Parser,
+    tag: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    let quasi = parse_template(parser, true)?;
+    ok_box(Node::TaggedTemplateExpression {
+        tag,
+        quasi,
+        span: parser.span_from(start),
+    })
 }
 
-fn get_level(token: &Token) -> Result<u8, String> {
-    let d = match token {
-        Token::Control(s) => match s.as_str() {
-            "." | "[" | "(" | "?." => 20,
-            "new" => 19,
-            "++" | "--" => 17,
-            "!" | "~" | "typeof" | "await" | "delete" => 16,
-            "**" => 15,
-            "*" | "/" | "%" => 14,
-            "+" | "-" => 13,
-            ">" | ">=" | "<" | "<=" => 11,
-            "==" | "!=" | "!==" | "===" => 10,
-            "&" => 9,
-            "^" => 8,
-            "|" => 7,
-            "&&" => 6,
-            "||" => 5,
-            "?" => 3,
-            "=" | "+=" | "-=" | "*=" | "/=" | "%=" => 2,
-            "," => 1,
-            _ => return Err(format!("get level err {token}")),
-        },
-        _ => return Err(format!("get level err {token}")),
+/// parses the operator after `left` and a right operand binding at least at `level`
+fn build_binary(
+    parser: &mut Parser,
+    left: Box<Node>,
+    start: usize,
+    level: u8,
+) -> Result<Box<Node>, ParseError> {
+    let operator = parser.current.to_string().to_lowercase();
+    parser.next();
+    let right = parse_expression(parser, level)?;
+    ok_box(Node::BinaryExpression {
+        operator,
+        left,
+        right,
+        span: parser.span_from(start),
+    })
+}
+
+fn build_postfix_update(
+    parser: &mut Parser,
+    left: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    let argument = simple_target(left)?;
+    let operator = parser.current.to_string();
+    parser.next();
+    ok_box(Node::UpdateExpression {
+        operator,
+        prefix: false,
+        argument,
+        span: parser.span_from(start),
+    })
+}
+
+/// parses `? b : c` after the test; either branch may be an assignment or an arrow function,
+/// like `a ? b = 1 : () => c`
+fn build_conditional(
+    parser: &mut Parser,
+    test: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    parser.next();
+    let consequent = with_for_init(parser, false, |parser| parse_expression(parser, 2))?;
+    if !is_punct(&parser.current, Punct::Colon) {
+        return Err(parser.unexpected(&["`:`"]));
+    }
+    parser.next();
+    let alternate = parse_expression(parser, 2)?;
+    ok_box(Node::ConditionalExpression {
+        test,
+        consequent,
+        alternate,
+        span: parser.span_from(start),
+    })
+}
+
+fn build_call(
+    parser: &mut Parser,
+    callee: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    let arguments = build_arguments(parser)?;
+    ok_box(Node::CallExpression {
+        callee,
+        arguments,
+        optional: false,
+        span: parser.span_from(start),
+    })
+}
+
+fn build_assignment(
+    parser: &mut Parser,
+    target: Box<Node>,
+    start: usize,
+    level: u8,
+) -> Result<Box<Node>, ParseError> {
+    let operator = parser.current.to_string();
+    // `[a, b] = c` destructures
+    let target = if operator == "=" && matches!(*target, Node::ArrayExpression { .. }) {
+        to_pattern(target, false)?
+    } else {
+        simple_target(target)?
     };
-    Ok(d)
+    parser.next();
+    // assignment is right-associative, like `**`
+    let right = parse_expression(parser, level)?;
+    ok_box(Node::AssignmentExpression {
+        operator,
+        left: target,
+        right,
+        span: parser.span_from(start),
+    })
 }
 
-pub fn is_ctrl_word(word: &Token, str: &str) -> bool {
-    match word {
-        Token::Control(s) => {
-            if s == str {
-                return true;
-            }
-            false
+/// parses `.name` or `[expression]` after `object`
+fn build_member(
+    parser: &mut Parser,
+    object: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    let computed = is_punct(&parser.current, Punct::LBracket);
+    let property = if computed {
+        build_computed_property(parser)?
+    } else {
+        parser.next();
+        build_dot_property(parser)?
+    };
+    ok_box(Node::MemberExpression {
+        object,
+        property,
+        computed,
+        optional: false,
+        span: parser.span_from(start),
+    })
+}
+
+/// parses `?.name`, `?.[expression]` or `?.(arguments)` after `object`
+fn build_optional(
+    parser: &mut Parser,
+    object: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    parser.next();
+    let span = |parser: &Parser| parser.span_from(start);
+    match parser.current {
+        Token::Punct(Punct::LParen) => ok_box(Node::CallExpression {
+            callee: object,
+            arguments: build_arguments(parser)?,
+            optional: true,
+            span: span(parser),
+        }),
+        Token::Punct(Punct::LBracket) => ok_box(Node::MemberExpression {
+            object,
+            property: build_computed_property(parser)?,
+            computed: true,
+            optional: true,
+            span: span(parser),
+        }),
+        _ => ok_box(Node::MemberExpression {
+            object,
+            property: build_dot_property(parser)?,
+            computed: false,
+            optional: true,
+            span: span(parser),
+        }),
+    }
+}
+
+fn build_logical(
+    parser: &mut Parser,
+    left: Box<Node>,
+    start: usize,
+    level: u8,
+) -> Result<Box<Node>, ParseError> {
+    let Token::Punct(operator) = parser.current else {
+        return Err(parser.unexpected(&["operator"]));
+    };
+    check_coalesce_mix(operator, &left, start)?;
+    parser.next();
+    let right_start = parser.current_span.start;
+    let right = parse_expression(parser, level + 1)?;
+    check_coalesce_mix(operator, &right, right_start)?;
+    ok_box(Node::LogicalExpression {
+        operator: operator.to_string(),
+        left,
+        right,
+        span: parser.span_from(start),
+    })
+}
+
+fn close_chain(
+    parser: &Parser,
+    expression: Box<Node>,
+    start: usize,
+) -> Result<Box<Node>, ParseError> {
+    ok_box(Node::ChainExpression {
+        expression,
+        span: parser.span_from(start),
+    })
+}
+
+/// `??` may not be mixed with `&&` or `||` without parentheses, like in `a ?? b || c`;
+/// an `operand` starting right at `operand_start` is not parenthesized
+fn check_coalesce_mix(
+    operator: Punct,
+    operand: &Node,
+    operand_start: usize,
+) -> Result<(), ParseError> {
+    if let Node::LogicalExpression {
+        operator: inner,
+        span,
+        ..
+    } = operand
+    {
+        let coalesce = operator == Punct::QuestionQuestion;
+        if span.start == operand_start && coalesce != (inner == "??") {
+            return Err(ParseError::MixedCoalesce { span: *span });
         }
-        _ => false,
     }
+    Ok(())
 }
 
-pub fn is_ctrl(word: &Token) -> bool {
-    match word {
-        Token::Control(_) => true,
-        _ => false,
+/// `-a ** b` could mean `(-a) ** b` or `-(a ** b)`, so a unary `base` starting right at
+/// `base_start` is not allowed
+fn check_exponent_base(base: &Node, base_start: usize) -> Result<(), ParseError> {
+    match base {
+        Node::UnaryExpression { span, .. } | Node::AwaitExpression { span, .. }
+            if span.start == base_start =>
+        {
+            Err(ParseError::UnaryBeforeExponent { span: *span })
+        }
+        _ => Ok(()),
     }
 }
 
-pub fn skip_empty(parser: &mut Parser) -> Token {
+/// parses `(a, ...b)`, the arguments of a call
+fn build_arguments(parser: &mut Parser) -> Result<Vec<Box<Node>>, ParseError> {
+    expect(parser, Punct::LParen)?;
+    parser.next();
+    let arguments = with_for_init(parser, false, |parser| {
+        let mut arguments: Vec<Box<Node>> = vec![];
+        while !is_punct(&parser.current, Punct::RParen) {
+            let argument_start = parser.current_span.start;
+            let express = if is_punct(&parser.current, Punct::Ellipsis) {
+                parser.next();
+                let argument = parse_expression(parser, 2)?;
+                Box::new(Node::SpreadElement {
+                    argument,
+                    span: parser.span_from(argument_start),
+                })
+            } else {
+                parse_expression(parser, 2)?
+            };
+            arguments.push(express);
+            if !is_punct(&parser.current, Punct::Comma) {
+                break;
+            }
+            parser.next();
+        }
+        Ok(arguments)
+    })?;
+    expect(parser, Punct::RParen)?;
+    parser.next();
+    Ok(arguments)
+}
+
+/// parses the name after `.` or `?.`, where any name fits, even a reserved word like
+/// `default`
+fn build_dot_property(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    let Some(name) = parser.current.identifier_name() else {
+        return Err(parser.unexpected(&["property name"]));
+    };
+    let property = Box::new(Node::Identity {
+        name,
+        span: parser.current_span,
+    });
+    parser.next();
+    Ok(property)
+}
+
+/// parses `[expression]`, the property of a computed member access
+fn build_computed_property(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    expect(parser, Punct::LBracket)?;
+    parser.next();
+    let property = with_for_init(parser, false, |parser| parse_expression(parser, 1))?;
+    expect(parser, Punct::RBracket)?;
+    parser.next();
+    Ok(property)
+}
+
+fn parse_template(parser: &mut Parser, tagged: bool) -> Result<Box<Node>, ParseError> {
+    let start = parser.current_span.start;
+    let mut quasis = vec![];
+    let mut expressions = vec![];
     loop {
-        match &parser.current {
-            Token::Control(next) => match next.as_str() {
-                "\r" | "\n" | " " | "\t" => {}
-                _ => break,
-            },
-            _ => break,
+        let Token::Template { cooked, raw, tail } = parser.current.clone() else {
+            return Err(parser.unexpected(&["template literal"]));
+        };
+        if cooked.is_none() && !tagged {
+            return Err(ParseError::InvalidTemplateEscape {
+                span: parser.current_span,
+            });
         }
+        // the element excludes the surrounding `\``, `}` and `${`
+        let span = parser.current_span;
+        let span = Span::new(span.start + 1, span.end - if tail { 1 } else { 2 });
+        quasis.push(Box::new(Node::TemplateElement {
+            raw: raw.into_owned(),
+            cooked: cooked.map(|c| c.into_owned()),
+            tail,
+            span,
+        }));
         parser.next();
+        if tail {
+            break;
+        }
+        expressions.push(with_for_init(parser, false, |parser| {
+            parse_expression(parser, 1)
+        })?);
+        expect(parser, Punct::RBrace)?;
+        parser.rescan_template();
     }
-    parser.current.clone()
+    ok_box(Node::TemplateLiteral {
+        quasis,
+        expressions,
+        span: parser.span_from(start),
+    })
 }
 
-pub fn expect(word: &Token, s: &str) -> Result<(), String> {
-    match word {
-        Token::Control(next) => {
-            if next != s {
-                return Err(format!("expect {s}"));
-            }
-        }
-        _ => return Err(format!("expect {s}")),
+/// parses a parenthesized expression, like the condition of `if` and `while`
+pub fn parse_paren_expression(parser: &mut Parser) -> Result<Box<Node>, ParseError> {
+    expect(parser, Punct::LParen)?;
+    parser.next();
+    let expression = parse_expression(parser, 1)?;
+    expect(parser, Punct::RParen)?;
+    parser.next();
+    Ok(expression)
+}
+
+/// checks that `node` can be assigned to or updated, like `a` or `a.b` but not `a + b`
+fn simple_target(node: Box<Node>) -> Result<Box<Node>, ParseError> {
+    match *node {
+        Node::Identity { .. } | Node::MemberExpression { .. } => Ok(node),
+        _ => Err(ParseError::InvalidAssignmentTarget { span: node.span() }),
+    }
+}
+
+pub fn ok_box(node: Node) -> Result<Box<Node>, ParseError> {
+    Ok(Box::new(node))
+}
+
+fn get_level(token: &Token) -> Option<u8> {
+    let d = match token {
+        Token::Punct(p) => match p {
+            Punct::Dot | Punct::LBracket | Punct::LParen | Punct::QuestionDot => 20,
+            Punct::PlusPlus | Punct::MinusMinus => 17,
+            Punct::Bang | Punct::Tilde => 16,
+            Punct::StarStar => 15,
+            Punct::Star | Punct::Slash | Punct::Percent => 14,
+            Punct::Plus | Punct::Minus => 13,
+            Punct::Shl | Punct::Shr | Punct::UShr => 12,
+            Punct::Gt | Punct::GtEq | Punct::Lt | Punct::LtEq => 11,
+            Punct::EqEq | Punct::NotEq | Punct::NotEqEq | Punct::EqEqEq => 10,
+            Punct::Amp => 9,
+            Punct::Caret => 8,
+            Punct::Pipe => 7,
+            Punct::AmpAmp => 6,
+            Punct::PipePipe | Punct::QuestionQuestion => 5,
+            Punct::Question => 3,
+            p if p.is_assignment() => 2,
+            Punct::Comma => 1,
+            _ => return None,
+        },
+        Token::Template { .. } => 20,
+        Token::Typeof | Token::Void | Token::Delete => 16,
+        Token::In | Token::Instanceof => 11,
+        _ => return None,
+    };
+    Some(d)
+}
+
+pub fn is_punct(word: &Token, punct: Punct) -> bool {
+    *word == Token::Punct(punct)
+}
+
+pub fn expect(parser: &Parser, punct: Punct) -> Result<(), ParseError> {
+    if !is_punct(&parser.current, punct) {
+        return Err(parser.unexpected(&[&format!("`{punct}`")]));
     }
     Ok(())
 }
 
-pub fn expect_keyword(word: &Token, token: Token) -> Result<(), String> {
-    if *word == token {
+pub fn expect_keyword(parser: &Parser, token: Token) -> Result<(), ParseError> {
+    if parser.current == token {
         return Ok(());
     }
-    Err(format!("expect keyword {token}"))
+    let keyword = token.to_string().to_lowercase();
+    Err(parser.unexpected(&[&format!("`{keyword}`")]))
 }
 
-pub fn expect_keys(word: &Token, list: &Vec<Token>) -> Result<Token, String> {
+pub fn expect_keys<'src>(
+    parser: &Parser<'src>,
+    list: &Vec<Token<'src>>,
+) -> Result<Token<'src>, ParseError> {
     for s in list {
-        if s == word {
+        if *s == parser.current {
             return Ok(s.clone());
         }
     }
-    Err(format!("expect {list:?}"))
+    let expected: Vec<String> = list
+        .iter()
+        .map(|s| format!("`{}`", s.to_string().to_lowercase()))
+        .collect();
+    let expected: Vec<&str> = expected.iter().map(|s| s.as_ref()).collect();
+    Err(parser.unexpected(&expected))
 }
@@ -1,35 +1,678 @@
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
 
+    use crate::error::ParseError;
     use crate::lex::Token;
     use crate::node::Node::*;
+    use crate::node::{Node, NumericValue};
     use crate::parser::Parser;
+    use crate::span::Span;
+
+    /// parses a program made of expression statements, returning their expressions
+    pub(crate) fn expressions(parser: &mut Parser) -> Vec<Box<Node>> {
+        let (ast, _) = parser.parse().unwrap();
+        ast.into_iter()
+            .map(|statement| match *statement {
+                ExpressionStatement { expression, .. } => expression,
+                _ => panic!("expect expression statement, find {statement:?}"),
+            })
+            .collect()
+    }
 
     #[test]
     fn test_dot() {
-        let mut parser = Parser::new("a.b.c".to_string());
-        let ast = parser.parse();
+        let mut parser = Parser::new("a.b.c");
+        parser.parse().unwrap();
         assert_eq!(parser.current, Token::EOF);
     }
 
     #[test]
     fn test_question() {
-        let mut parser = Parser::new("a = b ? c ? d : e : f".to_string());
-        let ast = parser.parse();
+        let mut parser = Parser::new("a = b ? c ? d : e : f");
+        parser.parse().unwrap();
         assert_eq!(parser.current, Token::EOF);
     }
 
+    #[test]
+    fn test_conditional_branches() {
+        let mut parser = Parser::new(
+            "x = a ? () => 1 : () => 2; a ? b = 1 : c; a ? b : c = d; a ? b : c ? d : e",
+        );
+        let ast = expressions(&mut parser);
+        let AssignmentExpression { right, .. } = &*ast[0] else {
+            panic!("expect assignment, find {:?}", ast[0]);
+        };
+        let ConditionalExpression {
+            consequent,
+            alternate,
+            ..
+        } = &**right
+        else {
+            panic!("expect conditional, find {right:?}");
+        };
+        assert!(matches!(**consequent, ArrowFunctionExpression { .. }));
+        assert!(matches!(**alternate, ArrowFunctionExpression { .. }));
+
+        let branches = ast[1..]
+            .iter()
+            .map(|node| match &**node {
+                ConditionalExpression {
+                    consequent,
+                    alternate,
+                    ..
+                } => (&**consequent, &**alternate),
+                _ => panic!("expect conditional, find {node:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(branches[0].0, AssignmentExpression { .. }));
+        assert!(matches!(branches[1].1, AssignmentExpression { .. }));
+        assert!(matches!(branches[2].1, ConditionalExpression { .. }));
+    }
+
     #[test]
     fn test_operator() {
-        let mut parser = Parser::new("c = a + +b + d++".to_string());
-        let ast = parser.parse();
+        let mut parser = Parser::new("c = a + +b + d++");
+        parser.parse().unwrap();
         assert_eq!(parser.current, Token::EOF);
     }
 
+    #[test]
+    fn test_exponent() {
+        let mut parser = Parser::new("a ** b ** c");
+        let ast = expressions(&mut parser);
+        let id = |name: &str, start| {
+            Box::new(Identity {
+                name: name.to_string(),
+                span: Span::new(start, start + 1),
+            })
+        };
+        assert_eq!(
+            ast,
+            vec![Box::new(BinaryExpression {
+                left: id("a", 0),
+                operator: "**".to_string(),
+                right: Box::new(BinaryExpression {
+                    left: id("b", 5),
+                    operator: "**".to_string(),
+                    right: id("c", 10),
+                    span: Span::new(5, 11),
+                }),
+                span: Span::new(0, 11),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_exponent_unary_base() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+        assert_eq!(
+            error("-a ** 2"),
+            ParseError::UnaryBeforeExponent {
+                span: Span::new(0, 2),
+            }
+        );
+        assert!(matches!(
+            error("x = 1 * typeof a ** 2"),
+            ParseError::UnaryBeforeExponent { .. }
+        ));
+        assert!(matches!(
+            error("async function f() { await a ** 2 }"),
+            ParseError::UnaryBeforeExponent { .. }
+        ));
+
+        let mut parser = Parser::new("(-a) ** 2; 2 ** -a; ++a ** 2");
+        let ast = expressions(&mut parser);
+        assert!(
+            ast.iter().all(
+                |node| matches!(&**node, BinaryExpression { operator, .. } if operator == "**")
+            )
+        );
+    }
+
     #[test]
     fn test_call() {
-        let mut parser = Parser::new("c = a ? b(d,e,f) : 2+3".to_string());
-        let ast = parser.parse();
+        let mut parser = Parser::new("c = a ? b(d,e,f) : 2+3");
+        parser.parse().unwrap();
         assert_eq!(parser.current, Token::EOF);
     }
+
+    #[test]
+    fn test_string() {
+        let mut parser = Parser::new(r#"a = "x\ty" + 'z'"#);
+        let ast = expressions(&mut parser);
+        assert_eq!(
+            ast,
+            vec![Box::new(AssignmentExpression {
+                left: Box::new(Identity {
+                    name: "a".to_string(),
+                    span: Span::new(0, 1),
+                }),
+                operator: "=".to_string(),
+                right: Box::new(BinaryExpression {
+                    left: Box::new(StringLiteral {
+                        value: "x\ty".to_string(),
+                        span: Span::new(4, 10),
+                    }),
+                    operator: "+".to_string(),
+                    right: Box::new(StringLiteral {
+                        value: "z".to_string(),
+                        span: Span::new(13, 16),
+                    }),
+                    span: Span::new(4, 16),
+                }),
+                span: Span::new(0, 16),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_template() {
+        let mut parser = Parser::new("`a${b + `c${d}`}e`");
+        let ast = expressions(&mut parser);
+        assert_eq!(parser.current, Token::EOF);
+        let element = |s: &str, tail, start| {
+            Box::new(TemplateElement {
+                raw: s.to_string(),
+                cooked: Some(s.to_string()),
+                tail,
+                span: Span::new(start, start + s.len()),
+            })
+        };
+        assert_eq!(
+            ast,
+            vec![Box::new(TemplateLiteral {
+                quasis: vec![element("a", false, 1), element("e", true, 16)],
+                expressions: vec![Box::new(BinaryExpression {
+                    left: Box::new(Identity {
+                        name: "b".to_string(),
+                        span: Span::new(4, 5),
+                    }),
+                    operator: "+".to_string(),
+                    right: Box::new(TemplateLiteral {
+                        quasis: vec![element("c", false, 9), element("", true, 14)],
+                        expressions: vec![Box::new(Identity {
+                            name: "d".to_string(),
+                            span: Span::new(12, 13),
+                        })],
+                        span: Span::new(8, 15),
+                    }),
+                    span: Span::new(4, 15),
+                })],
+                span: Span::new(0, 18),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_template_invalid_escape() {
+        let mut parser = Parser::new("`\\unicode`");
+        assert!(parser.parse().is_err());
+        let mut parser = Parser::new("`\\u{`");
+        assert_eq!(
+            parser.parse().unwrap_err(),
+            ParseError::InvalidTemplateEscape {
+                span: Span::new(0, 5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_tagged_template() {
+        let mut parser = Parser::new("a.b(c)`\\unicode and ${d}`");
+        let ast = expressions(&mut parser);
+        assert_eq!(parser.current, Token::EOF);
+        let TaggedTemplateExpression { tag, quasi, .. } = &*ast[0] else {
+            panic!("expect tagged template, find {ast:?}");
+        };
+        assert!(matches!(**tag, CallExpression { .. }));
+        let TemplateLiteral { quasis, .. } = &**quasi else {
+            panic!("expect template literal, find {quasi:?}");
+        };
+        assert_eq!(
+            *quasis[0],
+            TemplateElement {
+                raw: "\\unicode and ".into(),
+                cooked: None,
+                tail: false,
+                span: Span::new(7, 20),
+            }
+        );
+
+        let mut parser = Parser::new("tag`\\u{`");
+        let ast = expressions(&mut parser);
+        let TaggedTemplateExpression { quasi, .. } = &*ast[0] else {
+            panic!("expect tagged template, find {ast:?}");
+        };
+        let TemplateLiteral { quasis, .. } = &**quasi else {
+            panic!("expect template literal, find {quasi:?}");
+        };
+        assert!(matches!(*quasis[0], TemplateElement { cooked: None, .. }));
+    }
+
+    #[test]
+    fn test_numeric() {
+        let mut parser = Parser::new("1.5 * .5e1");
+        let ast = expressions(&mut parser);
+        assert_eq!(
+            ast,
+            vec![Box::new(BinaryExpression {
+                left: Box::new(NumericLiteral {
+                    value: NumericValue::Number(1.5),
+                    raw: "1.5".into(),
+                    span: Span::new(0, 3),
+                }),
+                operator: "*".to_string(),
+                right: Box::new(NumericLiteral {
+                    value: NumericValue::Number(5.0),
+                    raw: ".5e1".into(),
+                    span: Span::new(6, 10),
+                }),
+                span: Span::new(0, 10),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_regexp() {
+        let mut parser = Parser::new(r"s.replace(/[/]x\//g, y)");
+        let ast = expressions(&mut parser);
+        let id = |name: &str, start| {
+            Box::new(Identity {
+                name: name.to_string(),
+                span: Span::new(start, start + name.len()),
+            })
+        };
+        assert_eq!(
+            ast,
+            vec![Box::new(CallExpression {
+                callee: Box::new(MemberExpression {
+                    object: id("s", 0),
+                    property: id("replace", 2),
+                    computed: false,
+                    optional: false,
+                    span: Span::new(0, 9),
+                }),
+                arguments: vec![
+                    Box::new(RegExpLiteral {
+                        pattern: r"[/]x\/".to_string(),
+                        flags: "g".to_string(),
+                        span: Span::new(10, 19),
+                    }),
+                    id("y", 21),
+                ],
+                optional: false,
+                span: Span::new(0, 23),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_member() {
+        let mut parser = Parser::new("a.b[c].d; a.default; a\n.if[0]");
+        let ast = expressions(&mut parser);
+        let id = |name: &str, start| {
+            Box::new(Identity {
+                name: name.to_string(),
+                span: Span::new(start, start + name.len()),
+            })
+        };
+        let member = |object, property, computed, end| {
+            Box::new(MemberExpression {
+                object,
+                property,
+                computed,
+                optional: false,
+                span: Span::new(0, end),
+            })
+        };
+        // each access takes everything before it as its object
+        assert_eq!(
+            ast[0],
+            member(
+                member(
+                    member(id("a", 0), id("b", 2), false, 3),
+                    id("c", 4),
+                    true,
+                    6
+                ),
+                id("d", 7),
+                false,
+                8,
+            )
+        );
+        let MemberExpression { property, .. } = &*ast[1] else {
+            panic!("expect member expression, find {:?}", ast[1]);
+        };
+        assert_eq!(*property, id("default", 12));
+        let MemberExpression {
+            object, computed, ..
+        } = &*ast[2]
+        else {
+            panic!("expect member expression, find {:?}", ast[2]);
+        };
+        assert!(*computed);
+        assert!(matches!(**object, MemberExpression { .. }));
+    }
+
+    #[test]
+    fn test_member_errors() {
+        let mut parser = Parser::new("a.1");
+        assert!(parser.parse().is_err());
+        let mut parser = Parser::new("a[b");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn test_call_chain() {
+        let mut parser = Parser::new(
+            "f(a)(b); f().x; obj.method(1).then(2); promise.then(r => r.json()).catch(log)",
+        );
+        let ast = expressions(&mut parser);
+        // the outermost node is the last call or access of the chain
+        let shape = |node: &Node| match node {
+            CallExpression { callee, .. } => format!("call {}", callee.span().end),
+            MemberExpression { object, .. } => format!("member {}", object.span().end),
+            _ => panic!("expect call or member, find {node:?}"),
+        };
+        assert_eq!(
+            ast.iter().map(|node| shape(node)).collect::<Vec<_>>(),
+            vec!["call 4", "member 12", "call 34", "call 72"]
+        );
+        let CallExpression { callee, .. } = &*ast[0] else {
+            unreachable!();
+        };
+        assert!(matches!(**callee, CallExpression { .. }));
+    }
+
+    #[test]
+    fn test_spread_arguments() {
+        let mut parser = Parser::new("f(a, ...b); async(...c)");
+        let ast = expressions(&mut parser);
+        for node in &ast {
+            let CallExpression { arguments, .. } = &**node else {
+                panic!("expect call, find {node:?}");
+            };
+            assert!(matches!(
+                arguments.last().map(|a| &**a),
+                Some(SpreadElement { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_postfix() {
+        let mut parser = Parser::new("a++ + b; -a--; x = a.b[c]++ * 2; ++a.b");
+        let ast = expressions(&mut parser);
+        let BinaryExpression { left, .. } = &*ast[0] else {
+            panic!("expect binary expression, find {:?}", ast[0]);
+        };
+        assert!(matches!(**left, UpdateExpression { prefix: false, .. }));
+        let UnaryExpression { argument, .. } = &*ast[1] else {
+            panic!("expect unary expression, find {:?}", ast[1]);
+        };
+        assert!(matches!(**argument, UpdateExpression { .. }));
+        let AssignmentExpression { right, .. } = &*ast[2] else {
+            panic!("expect assignment, find {:?}", ast[2]);
+        };
+        assert!(matches!(**right, BinaryExpression { .. }));
+        assert!(matches!(*ast[3], UpdateExpression { prefix: true, .. }));
+    }
+
+    #[test]
+    fn test_postfix_errors() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert_eq!(
+            error("(a + b)++"),
+            ParseError::InvalidAssignmentTarget {
+                span: Span::new(1, 6),
+            }
+        );
+        assert!(matches!(
+            error("++a++"),
+            ParseError::InvalidAssignmentTarget { .. }
+        ));
+        assert!(matches!(error("a++.b"), ParseError::UnexpectedToken { .. }));
+        assert!(matches!(
+            error("a++(b)"),
+            ParseError::UnexpectedToken { .. }
+        ));
+    }
+
+    #[test]
+    fn test_optional_chain() {
+        let mut parser = Parser::new("a?.b.c; a?.[0]?.(1); a?.b + c; x = a.b?.c()");
+        let ast = expressions(&mut parser);
+        let ChainExpression { expression, span } = &*ast[0] else {
+            panic!("expect chain, find {:?}", ast[0]);
+        };
+        assert_eq!(*span, Span::new(0, 6));
+        let MemberExpression {
+            object, optional, ..
+        } = &**expression
+        else {
+            panic!("expect member expression, find {expression:?}");
+        };
+        assert!(!optional);
+        assert!(matches!(**object, MemberExpression { optional: true, .. }));
+
+        let ChainExpression { expression, .. } = &*ast[1] else {
+            panic!("expect chain, find {:?}", ast[1]);
+        };
+        let CallExpression {
+            callee, optional, ..
+        } = &**expression
+        else {
+            panic!("expect call, find {expression:?}");
+        };
+        assert!(*optional);
+        assert!(matches!(
+            **callee,
+            MemberExpression {
+                computed: true,
+                optional: true,
+                ..
+            }
+        ));
+
+        // the chain ends before a binary operator
+        let BinaryExpression { left, .. } = &*ast[2] else {
+            panic!("expect binary expression, find {:?}", ast[2]);
+        };
+        assert!(matches!(**left, ChainExpression { .. }));
+        let AssignmentExpression { right, .. } = &*ast[3] else {
+            panic!("expect assignment, find {:?}", ast[3]);
+        };
+        assert_eq!(right.span(), Span::new(35, 43));
+        assert!(matches!(**right, ChainExpression { .. }));
+    }
+
+    #[test]
+    fn test_optional_chain_errors() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert_eq!(
+            error("a?.b = 1"),
+            ParseError::InvalidAssignmentTarget {
+                span: Span::new(0, 4),
+            }
+        );
+        assert!(matches!(
+            error("a?.b`c`"),
+            ParseError::UnexpectedToken { .. }
+        ));
+        // `?.` before a digit is a conditional
+        assert!(matches!(error("a?.5"), ParseError::UnexpectedToken { .. }));
+    }
+
+    #[test]
+    fn test_logical() {
+        let mut parser = Parser::new("a || b && c; a ?? b ?? c; (a || b) ?? c; a ?? (b && c)");
+        let ast = expressions(&mut parser);
+        let operators = ast
+            .iter()
+            .map(|node| match &**node {
+                LogicalExpression {
+                    operator, right, ..
+                } => {
+                    let right = match &**right {
+                        LogicalExpression { operator, .. } => operator.as_str(),
+                        _ => "",
+                    };
+                    (operator.as_str(), right)
+                }
+                _ => panic!("expect logical expression, find {node:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            operators,
+            vec![("||", "&&"), ("??", ""), ("??", ""), ("??", "&&")]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_mix() {
+        let error = |input| Parser::new(input).parse().unwrap_err();
+
+        assert_eq!(
+            error("a ?? b || c"),
+            ParseError::MixedCoalesce {
+                span: Span::new(0, 6),
+            }
+        );
+        assert_eq!(
+            error("a || b ?? c"),
+            ParseError::MixedCoalesce {
+                span: Span::new(0, 6),
+            }
+        );
+        assert_eq!(
+            error("a ?? b && c"),
+            ParseError::MixedCoalesce {
+                span: Span::new(5, 11),
+            }
+        );
+        assert!(matches!(
+            error("a && (b) ?? c"),
+            ParseError::MixedCoalesce { .. }
+        ));
+    }
+
+    #[test]
+    fn test_regexp_after_update() {
+        // the lexer guesses a division after `++`, the parser knows better
+        let mut parser = Parser::new("x = ++/a/.b");
+        assert!(parser.parse().is_ok());
+        assert_eq!(parser.current, Token::EOF);
+    }
+
+    #[test]
+    fn test_division_after_keyword_property() {
+        let mut parser = Parser::new("x = a.default / 2 / 3; x = a.in / b; x = this.new / 2");
+        let ast = expressions(&mut parser);
+        for node in &ast {
+            let AssignmentExpression { right, .. } = &**node else {
+                panic!("expect assignment, find {node:?}");
+            };
+            assert!(
+                matches!(&**right, BinaryExpression { operator, .. } if operator == "/"),
+                "expect division, find {right:?}"
+            );
+        }
+
+        let mut parser = Parser::new("let x = this.new / 2");
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn test_keyword_expressions() {
+        let mut parser = Parser::new("let = typeof this in x ? null : !true");
+        let ast = expressions(&mut parser);
+        assert_eq!(
+            ast,
+            vec![Box::new(AssignmentExpression {
+                left: Box::new(Identity {
+                    name: "let".to_string(),
+                    span: Span::new(0, 3),
+                }),
+                operator: "=".to_string(),
+                right: Box::new(ConditionalExpression {
+                    test: Box::new(BinaryExpression {
+                        left: Box::new(UnaryExpression {
+                            operator: "typeof".to_string(),
+                            prefix: true,
+                            argument: Box::new(ThisExpression {
+                                span: Span::new(13, 17),
+                            }),
+                            span: Span::new(6, 17),
+                        }),
+                        operator: "in".to_string(),
+                        right: Box::new(Identity {
+                            name: "x".to_string(),
+                            span: Span::new(21, 22),
+                        }),
+                        span: Span::new(6, 22),
+                    }),
+                    consequent: Box::new(NullLiteral {
+                        span: Span::new(25, 29),
+                    }),
+                    alternate: Box::new(UnaryExpression {
+                        operator: "!".to_string(),
+                        prefix: true,
+                        argument: Box::new(BooleanLiteral {
+                            value: true,
+                            span: Span::new(33, 37),
+                        }),
+                        span: Span::new(32, 37),
+                    }),
+                    span: Span::new(6, 37),
+                }),
+                span: Span::new(0, 37),
+            })]
+        );
+    }
+
+    #[test]
+    fn test_invalid_assignment_target() {
+        let mut parser = Parser::new("a + b = c");
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::InvalidAssignmentTarget {
+                span: Span::new(0, 5)
+            })
+        );
+    }
+
+    #[test]
+    fn test_chained_assignment() {
+        let mut parser = Parser::new("a = b = c; a += b -= c");
+        let ast = expressions(&mut parser);
+        for node in &ast {
+            let AssignmentExpression { right, .. } = &**node else {
+                panic!("expect assignment, find {node:?}");
+            };
+            assert!(matches!(**right, AssignmentExpression { .. }));
+        }
+        assert_eq!(ast[0].span(), Span::new(0, 9));
+    }
+
+    #[test]
+    fn test_sequence() {
+        let mut parser = Parser::new("a = 1, b = 2; var c = 1, d = 2");
+        let (ast, _) = parser.parse().unwrap();
+        let ExpressionStatement { expression, .. } = &*ast[0] else {
+            panic!("expect expression statement, find {ast:?}");
+        };
+        let SequenceExpression { expressions, span } = &**expression else {
+            panic!("expect sequence, find {expression:?}");
+        };
+        assert_eq!(*span, Span::new(0, 12));
+        assert_eq!(expressions.len(), 2);
+        assert!(matches!(*expressions[1], AssignmentExpression { .. }));
+        // the comma of a declaration separates declarators instead
+        let VariableDeclaration { declarations, .. } = &*ast[1] else {
+            panic!("expect declaration, find {:?}", ast[1]);
+        };
+        assert_eq!(declarations.len(), 2);
+    }
 }
@@ -1,14 +1,19 @@
+use crate::error::ParseError;
+use crate::punct::Punct;
+use crate::span::{LineIndex, Span};
+use crate::unicode::{is_id_continue, is_id_start, is_line_terminator, is_whitespace};
+use std::borrow::Cow;
 use std::fmt::{Display, Formatter};
 
+/// a token whose text is borrowed from the source, unless escapes made it differ
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub enum Token {
-    LF,
-    CR,
+pub enum Token<'src> {
+    /// input that could not be lexed, with the diagnostic describing it
+    Error(Box<ParseError>),
 
     Var,
     Let,
     Const,
-    Undefined,
     Null,
     Await,
     Async,
@@ -21,19 +26,135 @@ pub enum Token {
     Continue,
     For,
     While,
+    Catch,
+    Class,
+    Debugger,
+    Default,
+    Delete,
+    Do,
+    Else,
+    Enum,
+    Export,
+    Extends,
+    False,
+    Finally,
+    Import,
+    In,
+    Instanceof,
+    New,
+    Return,
+    Super,
+    This,
+    Throw,
+    True,
+    Try,
+    Typeof,
+    Void,
+    Yield,
 
-    Variable(String),
-    Digit(String),
-    String,
-    Control(String),
+    Variable(Cow<'src, str>),
+    Digit(Cow<'src, str>),
+    String(Cow<'src, str>),
+    /// one piece of a template literal, ending at `${` or at the closing backtick;
+    /// `cooked` is None when it contains an escape only allowed in tagged templates
+    Template {
+        cooked: Option<Cow<'src, str>>,
+        raw: Cow<'src, str>,
+        tail: bool,
+    },
+    RegExp {
+        pattern: Cow<'src, str>,
+        flags: Cow<'src, str>,
+    },
+    Punct(Punct),
     EOF,
 }
 
-impl Display for Token {
+impl Token<'_> {
+    /// the name this token spells where reserved words are allowed too, like as a
+    /// property key
+    pub fn identifier_name(&self) -> Option<String> {
+        match self {
+            Token::Variable(name) => Some(name.to_string()),
+            Token::Error(_)
+            | Token::Digit(_)
+            | Token::String(_)
+            | Token::Template { .. }
+            | Token::RegExp { .. }
+            | Token::Punct(_)
+            | Token::EOF => None,
+            keyword => Some(keyword.to_string().to_lowercase()),
+        }
+    }
+
+    /// detaches the token from the source, e.g. to keep it in a `ParseError`
+    pub fn into_owned(self) -> Token<'static> {
+        let own = |s: Cow<'_, str>| Cow::Owned(s.into_owned());
+        match self {
+            Token::Error(e) => Token::Error(e),
+            Token::Var => Token::Var,
+            Token::Let => Token::Let,
+            Token::Const => Token::Const,
+            Token::Null => Token::Null,
+            Token::Await => Token::Await,
+            Token::Async => Token::Async,
+            Token::Function => Token::Function,
+            Token::With => Token::With,
+            Token::If => Token::If,
+            Token::Switch => Token::Switch,
+            Token::Case => Token::Case,
+            Token::Break => Token::Break,
+            Token::Continue => Token::Continue,
+            Token::For => Token::For,
+            Token::While => Token::While,
+            Token::Catch => Token::Catch,
+            Token::Class => Token::Class,
+            Token::Debugger => Token::Debugger,
+            Token::Default => Token::Default,
+            Token::Delete => Token::Delete,
+            Token::Do => Token::Do,
+            Token::Else => Token::Else,
+            Token::Enum => Token::Enum,
+            Token::Export => Token::Export,
+            Token::Extends => Token::Extends,
+            Token::False => Token::False,
+            Token::Finally => Token::Finally,
+            Token::Import => Token::Import,
+            Token::In => Token::In,
+            Token::Instanceof => Token::Instanceof,
+            Token::New => Token::New,
+            Token::Return => Token::Return,
+            Token::Super => Token::Super,
+            Token::This => Token::This,
+            Token::Throw => Token::Throw,
+            Token::True => Token::True,
+            Token::Try => Token::Try,
+            Token::Typeof => Token::Typeof,
+            Token::Void => Token::Void,
+            Token::Yield => Token::Yield,
+            Token::Variable(s) => Token::Variable(own(s)),
+            Token::Digit(s) => Token::Digit(own(s)),
+            Token::String(s) => Token::String(own(s)),
+            Token::Template { cooked, raw, tail } => Token::Template {
+                cooked: cooked.map(own),
+                raw: own(raw),
+                tail,
+            },
+            Token::RegExp { pattern, flags } => Token::RegExp {
+                pattern: own(pattern),
+                flags: own(flags),
+            },
+            Token::Punct(p) => Token::Punct(p),
+            Token::EOF => Token::EOF,
+        }
+    }
+}
+
+impl Display for Token<'_> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Token::Control(s) => {
-                write!(f, "{}", s)
+            Token::Punct(p) => {
+                write!(f, "{}", p)
             }
             Token::Variable(s) => {
                 write!(f, "{}", s)
@@ -41,10 +162,18 @@ impl Display for Token {
             Token::Digit(s) => {
                 write!(f, "{}", s)
             }
+            Token::String(s) => {
+                write!(f, "{:?}", s)
+            }
+            Token::Template { raw, .. } => {
+                write!(f, "`{}`", raw)
+            }
+            Token::RegExp { pattern, flags } => {
+                write!(f, "/{}/{}", pattern, flags)
+            }
             Token::Var => write!(f, "Var"),
             Token::Let => write!(f, "Let"),
             Token::Const => write!(f, "Const"),
-            Token::Undefined => write!(f, "Undefined"),
             Token::Null => write!(f, "Null"),
             Token::Await => write!(f, "Await"),
             Token::Async => write!(f, "Async"),
@@ -57,72 +186,309 @@ impl Display for Token {
             Token::Continue => write!(f, "Continue"),
             Token::For => write!(f, "For"),
             Token::While => write!(f, "While"),
+            Token::Catch => write!(f, "Catch"),
+            Token::Class => write!(f, "Class"),
+            Token::Debugger => write!(f, "Debugger"),
+            Token::Default => write!(f, "Default"),
+            Token::Delete => write!(f, "Delete"),
+            Token::Do => write!(f, "Do"),
+            Token::Else => write!(f, "Else"),
+            Token::Enum => write!(f, "Enum"),
+            Token::Export => write!(f, "Export"),
+            Token::Extends => write!(f, "Extends"),
+            Token::False => write!(f, "False"),
+            Token::Finally => write!(f, "Finally"),
+            Token::Import => write!(f, "Import"),
+            Token::In => write!(f, "In"),
+            Token::Instanceof => write!(f, "Instanceof"),
+            Token::New => write!(f, "New"),
+            Token::Return => write!(f, "Return"),
+            Token::Super => write!(f, "Super"),
+            Token::This => write!(f, "This"),
+            Token::Throw => write!(f, "Throw"),
+            Token::True => write!(f, "True"),
+            Token::Try => write!(f, "Try"),
+            Token::Typeof => write!(f, "Typeof"),
+            Token::Void => write!(f, "Void"),
+            Token::Yield => write!(f, "Yield"),
             Token::EOF => write!(f, "EOF"),
-            _ => {
-                write!(f, "token")
-            }
+            Token::Error(_) => write!(f, "Error"),
         }
     }
 }
 
-pub struct Lex {
-    input: String,
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CommentKind {
+    Line,
+    Block,
+    Hashbang,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Comment<'src> {
+    pub kind: CommentKind,
+    /// the comment body without its `//`, `/* */` or `#!` delimiters
+    pub text: &'src str,
+    pub span: Span,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct SpannedToken<'src> {
+    pub token: Token<'src>,
+    pub span: Span,
+    /// whether a line terminator separates the token from the previous one,
+    /// which is what automatic semicolon insertion looks at
+    pub newline_before: bool,
+}
+
+/// a single pass over the source with a byte cursor
+pub struct Lex<'src> {
+    input: &'src str,
+    /// byte offset of the next char to read
     pos: usize,
+    /// byte offset where the last token started
+    token_start: usize,
+    lines: LineIndex,
+    /// comments skipped so far, in source order
+    pub comments: Vec<Comment<'src>>,
+    /// byte offsets of legacy octal escapes (`\07`, `\8`) and legacy octal
+    /// numbers (`017`, `08`), which are errors in strict mode code
+    pub legacy_octal: Vec<usize>,
+    /// whether a `/` starts a regular expression (the RegExp goal) rather than
+    /// a division (the Div goal), judged from the previous token
+    regexp_allowed: bool,
+    /// whether the last token was `.` or `?.`, after which even a keyword is a property name
+    after_dot: bool,
+    /// whether a line terminator was skipped before the last token
+    newline_before: bool,
 }
-impl Lex {
-    pub fn new(input: String) -> Self {
-        Lex { input, pos: 0 }
-    }
-    pub fn next(&mut self) -> Token {
-        let str = &self.input;
-        if self.pos == str.len() {
-            return Token::EOF;
-        }
-        if self.pos > str.len() {
-            panic!("end of source");
+impl<'src> Lex<'src> {
+    pub fn new(input: &'src str) -> Self {
+        Lex {
+            lines: LineIndex::new(input),
+            input,
+            pos: 0,
+            token_start: 0,
+            comments: vec![],
+            legacy_octal: vec![],
+            regexp_allowed: true,
+            after_dot: false,
+            newline_before: false,
         }
+    }
+    // unlike `Iterator::next`, it keeps returning `Token::EOF` at the end
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Token<'src> {
+        self.newline_before = false;
+        let token = match self.read_token() {
+            Ok(token) => token,
+            Err(e) => Token::Error(Box::new(e)),
+        };
+        // a division may follow a property, like `default` in `a.default / 2`
+        let property = self.after_dot && !matches!(token, Token::Punct(_) | Token::Template { .. });
+        self.regexp_allowed = !property && allows_regexp(&token);
+        self.after_dot = matches!(token, Token::Punct(Punct::Dot | Punct::QuestionDot));
+        token
+    }
+
+    fn read_token(&mut self) -> Result<Token<'src>, ParseError> {
+        let str = self.input;
         loop {
-            let c = str.chars().nth(self.pos);
+            self.token_start = self.pos;
+            let c = char_at(str, self.pos);
             match c {
                 Some(c) => match c {
-                    ' ' | '\r' | '\n' => self.pos += 1,
-                    '=' | '+' | '-' | '*' | '/' | '%' | '>' | '<' | '|' | '?' | ':' => {
-                        return read_operation(&mut self.pos, &str);
+                    c if is_line_terminator(c) => {
+                        self.newline_before = true;
+                        self.pos += c.len_utf8();
+                    }
+                    c if is_whitespace(c) => self.pos += c.len_utf8(),
+                    '/' if matches!(char_at(str, self.pos + 1), Some('/' | '*')) => {
+                        let comment = read_comment(&mut self.pos, str)?;
+                        // a block comment spanning lines counts as a line terminator
+                        if comment.text.contains(is_line_terminator) {
+                            self.newline_before = true;
+                        }
+                        self.comments.push(comment);
                     }
-                    ';' | '(' | ')' | '{' | '}' | '.' | '!' | ',' => {
+                    '/' if self.regexp_allowed => return read_regexp(&mut self.pos, str),
+                    '#' if self.pos == 0 && char_at(str, 1) == Some('!') => {
+                        let comment = read_comment(&mut self.pos, str)?;
+                        self.comments.push(comment);
+                    }
+                    '.' if matches!(char_at(str, self.pos + 1), Some('0'..='9')) => {
+                        return read_digit(&mut self.pos, str, &mut self.legacy_octal);
+                    }
+                    '0'..='9' => return read_digit(&mut self.pos, str, &mut self.legacy_octal),
+                    c if c == '\\' || is_id_start(c) => return read_word(&mut self.pos, str),
+                    '\'' | '"' => return read_string(&mut self.pos, str, &mut self.legacy_octal),
+                    '`' => {
                         self.pos += 1;
-                        return Token::Control(c.to_string());
+                        return read_template(&mut self.pos, str);
+                    }
+                    _ => {
+                        if let Some((punct, len)) = Punct::longest(&str[self.pos..]) {
+                            self.pos += len;
+                            return Ok(Token::Punct(punct));
+                        }
+                        self.pos += c.len_utf8();
+                        return Err(ParseError::UnexpectedCharacter {
+                            found: c,
+                            span: Span::new(self.token_start, self.pos),
+                        });
                     }
-                    '_' | 'a'..='z' | 'A'..='Z' => return read_word(&mut self.pos, &str),
-                    '0'..='9' => return read_digit(&mut self.pos, &str),
-                    _ => panic!("Unrecognized character {c}"),
                 },
-                None => return Token::EOF,
+                None => return Ok(Token::EOF),
             }
         }
     }
+
+    pub fn next_token(&mut self) -> SpannedToken<'src> {
+        let token = self.next();
+        SpannedToken {
+            token,
+            span: Span::new(self.token_start, self.pos),
+            newline_before: self.newline_before,
+        }
+    }
+
+    /// re-reads the source after the `}` that closes a template substitution,
+    /// which `next` would otherwise lex as code
+    pub fn rescan_template(&mut self, pos: usize) -> SpannedToken<'src> {
+        self.pos = pos;
+        let token = match read_template(&mut self.pos, self.input) {
+            Ok(token) => token,
+            Err(e) => Token::Error(Box::new(e)),
+        };
+        self.regexp_allowed = allows_regexp(&token);
+        self.after_dot = false;
+        SpannedToken {
+            token,
+            span: Span::new(pos - 1, self.pos),
+            newline_before: false,
+        }
+    }
+
+    /// re-reads the `/` or `/=` at `pos` as the start of a regular expression,
+    /// for when the parser expects an expression where `next` guessed a division
+    pub fn rescan_regexp(&mut self, pos: usize) -> SpannedToken<'src> {
+        self.pos = pos;
+        let token = match read_regexp(&mut self.pos, self.input) {
+            Ok(token) => token,
+            Err(e) => Token::Error(Box::new(e)),
+        };
+        self.regexp_allowed = false;
+        self.after_dot = false;
+        SpannedToken {
+            token,
+            span: Span::new(pos, self.pos),
+            newline_before: false,
+        }
+    }
+
+    /// the 1-based line and column of a byte offset
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        self.lines.line_col(self.input, offset)
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
 }
 
-fn read_word(i: &mut usize, source: &str) -> Token {
-    let c = source.chars().nth(*i).unwrap();
-    let mut word = String::new();
-    word.push(c);
-    loop {
-        *i = *i + 1;
-        let d = source.chars().nth(*i);
-        match d {
-            Some(d) => match d {
-                '_' | 'a'..='z' | 'A'..='Z' | '0'..='9' => word.push(d),
-                _ => break,
-            },
-            None => break,
+/// whether a `/` after `token` starts a regular expression; after `)`, `]` and `}`
+/// it is usually a division, and the parser rescans it when it is not
+fn allows_regexp(token: &Token) -> bool {
+    match token {
+        Token::Variable(_)
+        | Token::Digit(_)
+        | Token::String(_)
+        | Token::RegExp { .. }
+        | Token::Null
+        | Token::True
+        | Token::False
+        | Token::This
+        | Token::Super => false,
+        Token::Template { tail, .. } => !tail,
+        Token::Punct(p) => !matches!(
+            p,
+            Punct::RParen | Punct::RBracket | Punct::RBrace | Punct::PlusPlus | Punct::MinusMinus
+        ),
+        _ => true,
+    }
+}
+
+/// the char starting at byte offset `i`
+fn char_at(source: &str, i: usize) -> Option<char> {
+    source.get(i..)?.chars().next()
+}
+
+fn read_word<'src>(i: &mut usize, source: &'src str) -> Result<Token<'src>, ParseError> {
+    let start = *i;
+    // only allocated once an escape makes the name differ from the source text
+    let mut name: Option<String> = None;
+    // the first bad escape, reported once the whole identifier is skipped
+    let mut error = None;
+    while let Some(c) = char_at(source, *i) {
+        let first = *i == start;
+        if c == '\\' {
+            let escape_start = *i;
+            let name = name.get_or_insert_with(|| source[start..escape_start].to_string());
+            *i += 1;
+            let mut decoded = None;
+            if char_at(source, *i) == Some('u') {
+                *i += 1;
+                decoded = read_unicode_escape(i, source).ok().and_then(char::from_u32);
+            }
+            match decoded {
+                Some(c) if (first && is_id_start(c)) || (!first && is_id_continue(c)) => {
+                    name.push(c)
+                }
+                _ => {
+                    error.get_or_insert(ParseError::InvalidEscape {
+                        reason: "invalid escape sequence in identifier",
+                        span: Span::new(escape_start, *i),
+                    });
+                }
+            }
+            continue;
+        }
+        if (first && !is_id_start(c)) || !is_id_continue(c) {
+            break;
+        }
+        *i += c.len_utf8();
+        if let Some(name) = &mut name {
+            name.push(c);
         }
     }
-    match word.as_str() {
+    if let Some(error) = error {
+        return Err(error);
+    }
+    let name = match name {
+        Some(name) => name,
+        None => {
+            let word = &source[start..*i];
+            return Ok(keyword(word).unwrap_or(Token::Variable(Cow::Borrowed(word))));
+        }
+    };
+    match keyword(&name) {
+        // only reserved words are forbidden, escaped contextual keywords are identifiers
+        Some(Token::Async | Token::Let | Token::Await | Token::Yield) | None => {
+            Ok(Token::Variable(Cow::Owned(name)))
+        }
+        Some(_) => Err(ParseError::EscapedKeyword {
+            span: Span::new(start, *i),
+        }),
+    }
+}
+
+/// the reserved words, plus `let` and `async` which the parser reads as identifiers
+/// where they cannot be keywords; `of`, `get` and `set` stay identifiers
+fn keyword(word: &str) -> Option<Token<'static>> {
+    let token = match word {
         "var" => Token::Var,
         "let" => Token::Let,
         "const" => Token::Const,
-        "undefined" => Token::Undefined,
         "null" => Token::Null,
         "await" => Token::Await,
         "async" => Token::Async,
@@ -135,75 +501,507 @@ fn read_word(i: &mut usize, source: &str) -> Token {
         "continue" => Token::Continue,
         "for" => Token::For,
         "while" => Token::While,
-        _ => Token::Variable(word),
+        "catch" => Token::Catch,
+        "class" => Token::Class,
+        "debugger" => Token::Debugger,
+        "default" => Token::Default,
+        "delete" => Token::Delete,
+        "do" => Token::Do,
+        "else" => Token::Else,
+        "enum" => Token::Enum,
+        "export" => Token::Export,
+        "extends" => Token::Extends,
+        "false" => Token::False,
+        "finally" => Token::Finally,
+        "import" => Token::Import,
+        "in" => Token::In,
+        "instanceof" => Token::Instanceof,
+        "new" => Token::New,
+        "return" => Token::Return,
+        "super" => Token::Super,
+        "this" => Token::This,
+        "throw" => Token::Throw,
+        "true" => Token::True,
+        "try" => Token::Try,
+        "typeof" => Token::Typeof,
+        "void" => Token::Void,
+        "yield" => Token::Yield,
+        _ => return None,
+    };
+    Some(token)
+}
+
+fn read_digit<'src>(
+    i: &mut usize,
+    source: &'src str,
+    legacy_octal: &mut Vec<usize>,
+) -> Result<Token<'src>, ParseError> {
+    let start = *i;
+    match scan_number(i, source, legacy_octal) {
+        Ok(()) => Ok(Token::Digit(Cow::Borrowed(&source[start..*i]))),
+        Err(reason) => {
+            // skip the rest of the malformed literal so lexing resumes after it
+            while let Some(c) = char_at(source, *i).filter(|&c| c == '.' || is_id_continue(c)) {
+                *i += c.len_utf8();
+            }
+            Err(ParseError::InvalidNumber {
+                reason,
+                span: Span::new(start, *i),
+            })
+        }
     }
 }
 
-fn read_operation(i: &mut usize, source: &str) -> Token {
-    let c = source.chars().nth(*i).unwrap();
-    let mut word = String::new();
-    word.push(c);
+/// moves `i` past a numeric literal, which is ASCII only
+fn scan_number(
+    i: &mut usize,
+    source: &str,
+    legacy_octal: &mut Vec<usize>,
+) -> Result<(), &'static str> {
+    let start = *i;
+    let bytes = source.as_bytes();
+    let c = bytes[*i];
+    let next = bytes.get(*i + 1).copied();
+    let radix = match (c, next) {
+        (b'0', Some(b'x' | b'X')) => 16,
+        (b'0', Some(b'o' | b'O')) => 8,
+        (b'0', Some(b'b' | b'B')) => 2,
+        _ => 10,
+    };
+    if radix != 10 {
+        *i += 2;
+        if read_digits(i, source, start, radix, true)? == 0 {
+            return Err("missing digits after the radix prefix");
+        }
+        if bytes.get(*i) == Some(&b'n') {
+            *i += 1;
+        }
+    } else {
+        let mut bigint_allowed = true;
+        let mut legacy = false;
+        if c == b'0' && matches!(next, Some(b'0'..=b'9')) {
+            // legacy octal like `017`, or a decimal with a leading zero like `089`
+            legacy_octal.push(start);
+            bigint_allowed = false;
+            read_digits(i, source, start, 10, false)?;
+            legacy = source[start..*i].bytes().all(|c| c < b'8');
+        } else if c != b'.' {
+            read_digits(i, source, start, 10, true)?;
+        }
+        if bytes.get(*i) == Some(&b'.') && !legacy {
+            *i += 1;
+            bigint_allowed = false;
+            if read_digits(i, source, start, 10, true)? == 0 && c == b'.' {
+                return Err("missing digits after `.`");
+            }
+        }
+        if matches!(bytes.get(*i), Some(b'e' | b'E')) && !legacy {
+            *i += 1;
+            bigint_allowed = false;
+            if let Some(b'+' | b'-') = bytes.get(*i) {
+                *i += 1;
+            }
+            if read_digits(i, source, start, 10, true)? == 0 {
+                return Err("missing exponent digits");
+            }
+        }
+        if bytes.get(*i) == Some(&b'n') {
+            if !bigint_allowed {
+                return Err("a BigInt literal must be an integer without leading zeros");
+            }
+            *i += 1;
+        }
+    }
+    if char_at(source, *i).is_some_and(|c| c == '\\' || c.is_ascii_digit() || is_id_start(c)) {
+        return Err("an identifier starts immediately after the number");
+    }
+    Ok(())
+}
+
+/// moves `i` past digits of `radix`, returning how many there were
+fn read_digits(
+    i: &mut usize,
+    source: &str,
+    number_start: usize,
+    radix: u32,
+    separators: bool,
+) -> Result<usize, &'static str> {
+    let bytes = source.as_bytes();
+    let is_digit = |b: Option<&u8>| b.is_some_and(|b| (*b as char).is_digit(radix));
+    let mut count = 0;
     loop {
-        *i = *i + 1;
-        let c = source.chars().nth(*i);
-        match c {
-            Some(c) => match c {
-                '=' | '+' | '-' | '*' | '/' | '%' | '>' | '<' | '|' | '?' | ':' => {
-                    word.push(c);
+        match bytes.get(*i) {
+            Some(b'_') if separators => {
+                // only between two digits, and never after a leading `0`
+                let after_zero = &source[number_start..*i] == "0";
+                if count == 0 || after_zero || !is_digit(bytes.get(*i + 1)) {
+                    return Err("numeric separators are not allowed here");
                 }
-                _ => {
-                    break;
-                }
-            },
-            None => break,
+            }
+            b if is_digit(b) => count += 1,
+            _ => break,
         }
+        *i += 1;
     }
-    Token::Control(word)
+    Ok(count)
 }
 
-fn read_newline(i: &mut usize, source: &str) -> Token {
+fn read_comment<'src>(i: &mut usize, source: &'src str) -> Result<Comment<'src>, ParseError> {
+    let start = *i;
+    let kind = match char_at(source, *i + 1) {
+        Some('*') => CommentKind::Block,
+        Some('!') => CommentKind::Hashbang,
+        _ => CommentKind::Line,
+    };
+    *i += 2;
+    let text_start = *i;
+    let text_end;
     loop {
-        *i = *i + 1;
-        let c = source.chars().nth(*i);
+        let c = char_at(source, *i);
         match c {
-            Some(c) => match c {
-                '\r' | '\n' | ' ' | '\t' => {}
-                _ => break,
-            },
-            _ => break,
+            Some('*') if kind == CommentKind::Block && char_at(source, *i + 1) == Some('/') => {
+                text_end = *i;
+                *i += 2;
+                break;
+            }
+            Some('\r' | '\n' | '\u{2028}' | '\u{2029}') if kind != CommentKind::Block => {
+                text_end = *i;
+                break;
+            }
+            Some(c) => *i += c.len_utf8(),
+            None if kind == CommentKind::Block => {
+                return Err(ParseError::UnterminatedLiteral {
+                    kind: "comment",
+                    span: Span::new(start, *i),
+                });
+            }
+            None => {
+                text_end = *i;
+                break;
+            }
         }
     }
-    Token::Control("\n".to_string())
+    Ok(Comment {
+        kind,
+        text: &source[text_start..text_end],
+        span: Span::new(start, *i),
+    })
 }
 
-fn read_digit(i: &mut usize, source: &str) -> Token {
-    let c = source.chars().nth(*i).unwrap();
-    let mut word = String::new();
-    word.push(c);
+fn read_string<'src>(
+    i: &mut usize,
+    source: &'src str,
+    legacy_octal: &mut Vec<usize>,
+) -> Result<Token<'src>, ParseError> {
+    let begin = *i;
+    let quote = char_at(source, *i).unwrap();
+    *i += 1;
+    // only allocated once an escape makes the value differ from the source text
+    let mut value: Option<String> = None;
+    // the first bad escape, reported once the whole literal is skipped
+    let mut error = None;
     loop {
-        *i = *i + 1;
-        let c = source.chars().nth(*i);
+        let c = char_at(source, *i);
         match c {
-            Some(c) => match c {
-                '_' => {
-                    word.push(c);
+            Some(c) if c == quote => {
+                *i += 1;
+                break;
+            }
+            Some('\\') => {
+                let start = *i;
+                let value = value.get_or_insert_with(|| source[begin + 1..start].to_string());
+                *i += 1;
+                match read_escape(i, source) {
+                    Ok(Escape::Char(c)) => value.push(c),
+                    Ok(Escape::LineContinuation) => {}
+                    Ok(Escape::LegacyOctal(c)) => {
+                        legacy_octal.push(start);
+                        value.push(c);
+                    }
+                    Err(reason) => {
+                        error.get_or_insert(ParseError::InvalidEscape {
+                            reason,
+                            span: Span::new(start, *i),
+                        });
+                    }
                 }
-                '0'..='9' => {
-                    word.push(c);
+            }
+            Some('\r') | Some('\n') | None => {
+                return Err(ParseError::UnterminatedLiteral {
+                    kind: "string",
+                    span: Span::new(begin, *i),
+                });
+            }
+            Some(c) => {
+                *i += c.len_utf8();
+                if let Some(value) = &mut value {
+                    value.push(c);
                 }
-                _ => {
-                    break;
+            }
+        }
+    }
+    if let Some(error) = error {
+        return Err(error);
+    }
+    Ok(Token::String(match value {
+        Some(value) => Cow::Owned(value),
+        None => Cow::Borrowed(&source[begin + 1..*i - 1]),
+    }))
+}
+
+fn read_template<'src>(i: &mut usize, source: &'src str) -> Result<Token<'src>, ParseError> {
+    let begin = *i;
+    // both stay borrowed until an escape or a `\r` makes them differ from the source text
+    let mut cooked: Option<String> = None;
+    let mut raw: Option<String> = None;
+    let mut valid = true;
+    loop {
+        let c = char_at(source, *i);
+        let tail = match c {
+            Some('`') => true,
+            Some('$') if char_at(source, *i + 1) == Some('{') => false,
+            Some('\\') => {
+                let start = *i;
+                *i += 1;
+                let escape = read_escape(i, source);
+                let value = cooked.get_or_insert_with(|| source[begin..start].to_string());
+                match escape {
+                    Ok(Escape::Char(c)) => value.push(c),
+                    Ok(Escape::LineContinuation) => {}
+                    _ => valid = false,
                 }
-            },
-            None => break,
+                let text = &source[start..*i];
+                if raw.is_some() || text.contains('\r') {
+                    let raw = raw.get_or_insert_with(|| source[begin..start].to_string());
+                    raw.push_str(&text.replace("\r\n", "\n").replace('\r', "\n"));
+                }
+                continue;
+            }
+            Some('\r') => {
+                let start = *i;
+                *i += 1;
+                if char_at(source, *i) == Some('\n') {
+                    *i += 1;
+                }
+                cooked
+                    .get_or_insert_with(|| source[begin..start].to_string())
+                    .push('\n');
+                raw.get_or_insert_with(|| source[begin..start].to_string())
+                    .push('\n');
+                continue;
+            }
+            Some(c) => {
+                *i += c.len_utf8();
+                if let Some(cooked) = &mut cooked {
+                    cooked.push(c);
+                }
+                if let Some(raw) = &mut raw {
+                    raw.push(c);
+                }
+                continue;
+            }
+            None => {
+                return Err(ParseError::UnterminatedLiteral {
+                    kind: "template",
+                    span: Span::new(begin - 1, *i),
+                });
+            }
+        };
+        let text = &source[begin..*i];
+        *i += if tail { 1 } else { 2 };
+        let cooked = match cooked {
+            _ if !valid => None,
+            Some(cooked) => Some(Cow::Owned(cooked)),
+            None => Some(Cow::Borrowed(text)),
+        };
+        let raw = match raw {
+            Some(raw) => Cow::Owned(raw),
+            None => Cow::Borrowed(text),
+        };
+        return Ok(Token::Template { cooked, raw, tail });
+    }
+}
+
+fn read_regexp<'src>(i: &mut usize, source: &'src str) -> Result<Token<'src>, ParseError> {
+    let begin = *i;
+    *i += 1;
+    let mut in_class = false;
+    loop {
+        let c = char_at(source, *i);
+        match c {
+            Some('/') if !in_class => break,
+            Some('[') => in_class = true,
+            Some(']') => in_class = false,
+            Some('\\') => {
+                *i += 1;
+                match char_at(source, *i) {
+                    Some('\r' | '\n' | '\u{2028}' | '\u{2029}') | None => {}
+                    Some(c) => *i += c.len_utf8(),
+                }
+                continue;
+            }
+            _ => {}
+        }
+        match c {
+            Some('\r' | '\n' | '\u{2028}' | '\u{2029}') | None => {
+                return Err(ParseError::UnterminatedLiteral {
+                    kind: "regular expression",
+                    span: Span::new(begin, *i),
+                });
+            }
+            Some(c) => *i += c.len_utf8(),
         }
     }
-    Token::Digit(word)
+    let pattern = &source[begin + 1..*i];
+    *i += 1;
+    let flags_start = *i;
+    while let Some(c) = char_at(source, *i).filter(|&c| c == '\\' || is_id_continue(c)) {
+        *i += c.len_utf8();
+    }
+    let flags = &source[flags_start..*i];
+    let mut seen = String::new();
+    for c in flags.chars() {
+        let reason = match c {
+            _ if seen.contains(c) => "duplicate flag",
+            'd' | 'g' | 'i' | 'm' | 's' | 'u' | 'v' | 'y' => {
+                seen.push(c);
+                continue;
+            }
+            _ => "unknown flag",
+        };
+        return Err(ParseError::InvalidRegExp {
+            reason,
+            span: Span::new(flags_start, *i),
+        });
+    }
+    if seen.contains('u') && seen.contains('v') {
+        return Err(ParseError::InvalidRegExp {
+            reason: "the `u` and `v` flags cannot be combined",
+            span: Span::new(flags_start, *i),
+        });
+    }
+    Ok(Token::RegExp {
+        pattern: Cow::Borrowed(pattern),
+        flags: Cow::Borrowed(flags),
+    })
+}
+
+pub enum Escape {
+    Char(char),
+    LineContinuation,
+    LegacyOctal(char),
+}
+
+/// reads the escape sequence after a `\`, leaving `i` on the char that follows it
+pub fn read_escape(i: &mut usize, source: &str) -> Result<Escape, &'static str> {
+    let c = match char_at(source, *i) {
+        Some(c) => c,
+        None => return Err("unterminated escape sequence"),
+    };
+    *i += c.len_utf8();
+    let escape = match c {
+        'n' => Escape::Char('\n'),
+        't' => Escape::Char('\t'),
+        'r' => Escape::Char('\r'),
+        'b' => Escape::Char('\u{8}'),
+        'f' => Escape::Char('\u{c}'),
+        'v' => Escape::Char('\u{b}'),
+        '\r' => {
+            if char_at(source, *i) == Some('\n') {
+                *i += 1;
+            }
+            Escape::LineContinuation
+        }
+        '\n' | '\u{2028}' | '\u{2029}' => Escape::LineContinuation,
+        'x' => {
+            let code = read_hex(i, source, 2)?;
+            Escape::Char(char::from_u32(code).unwrap())
+        }
+        'u' => {
+            let code = read_unicode_escape(i, source)?;
+            // a high surrogate followed by `\uDC00`-`\uDFFF` is one code point
+            if (0xD800..0xDC00).contains(&code) && source[*i..].starts_with("\\u") {
+                let mut j = *i + 2;
+                if let Ok(low) = read_unicode_escape(&mut j, source)
+                    && (0xDC00..0xE000).contains(&low)
+                {
+                    *i = j;
+                    let code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                    return Ok(Escape::Char(char::from_u32(code).unwrap()));
+                }
+            }
+            Escape::Char(char::from_u32(code).unwrap_or('\u{FFFD}'))
+        }
+        '0' if !matches!(char_at(source, *i), Some('0'..='9')) => Escape::Char('\0'),
+        '0'..='7' => {
+            // up to three octal digits, with a value no greater than 0o377
+            let mut code = c.to_digit(8).unwrap();
+            let max = if c <= '3' { 2 } else { 1 };
+            for _ in 0..max {
+                match char_at(source, *i).and_then(|d| d.to_digit(8)) {
+                    Some(d) => {
+                        code = code * 8 + d;
+                        *i += 1;
+                    }
+                    None => break,
+                }
+            }
+            Escape::LegacyOctal(char::from_u32(code).unwrap())
+        }
+        '8' | '9' => Escape::LegacyOctal(c),
+        _ => Escape::Char(c),
+    };
+    Ok(escape)
+}
+
+/// reads `XXXX` or `{X...}` after `\u`
+fn read_unicode_escape(i: &mut usize, source: &str) -> Result<u32, &'static str> {
+    if char_at(source, *i) != Some('{') {
+        return read_hex(i, source, 4);
+    }
+    *i += 1;
+    let mut code: u32 = 0;
+    let mut digits = 0;
+    loop {
+        match char_at(source, *i) {
+            Some('}') if digits > 0 => {
+                *i += 1;
+                break;
+            }
+            Some(c) if c.is_ascii_hexdigit() => {
+                *i += 1;
+                code = code * 16 + c.to_digit(16).unwrap();
+                digits += 1;
+                if code > 0x10FFFF {
+                    return Err("undefined Unicode code point");
+                }
+            }
+            // the offending character is left for the literal, which may end with it
+            _ => return Err("invalid Unicode escape sequence"),
+        }
+    }
+    Ok(code)
+}
+
+fn read_hex(i: &mut usize, source: &str, len: usize) -> Result<u32, &'static str> {
+    let mut code = 0;
+    for _ in 0..len {
+        match char_at(source, *i).and_then(|c| c.to_digit(16)) {
+            Some(d) => code = code * 16 + d,
+            None => return Err("invalid hexadecimal escape sequence"),
+        }
+        *i += 1;
+    }
+    Ok(code)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::lex::{Lex, Token};
+    use crate::error::ParseError;
+    use crate::lex::{Comment, CommentKind, Lex, SpannedToken, Token};
+    use crate::punct::Punct;
+    use crate::span::Span;
+    use std::borrow::Cow;
 
     #[test]
     fn test_token_display() {
@@ -214,21 +1012,447 @@ mod tests {
     #[test]
     fn test_keyword() {
         let input = "for(let i = 1; i < 10;i++)++";
-        let mut lex = Lex::new(input.to_string());
+        let mut lex = Lex::new(input);
         assert_eq!(lex.next(), Token::For);
+
+        let mut lex = Lex::new("this instanceof typeof of get undefined async yield");
+        assert_eq!(lex.next(), Token::This);
+        assert_eq!(lex.next(), Token::Instanceof);
+        assert_eq!(lex.next(), Token::Typeof);
+        for name in ["of", "get", "undefined"] {
+            assert_eq!(lex.next(), Token::Variable(name.into()));
+        }
+        assert_eq!(lex.next(), Token::Async);
+        assert_eq!(lex.next(), Token::Yield);
     }
 
     #[test]
     fn test_lex() {
         let input = " \n\n\nlet\n\n\n a\n\n\n =\n\n\n 1\n\n\n + \n\n\n2\n\n\n";
-        let mut lex = Lex::new(input.to_string());
+        let mut lex = Lex::new(input);
 
         assert_eq!(lex.next(), Token::Let);
-        assert_eq!(lex.next(), Token::Variable("a".to_string()));
-        assert_eq!(lex.next(), Token::Control("=".to_string()));
-        assert_eq!(lex.next(), Token::Digit("1".to_string()));
-        assert_eq!(lex.next(), Token::Control("+".to_string()));
-        assert_eq!(lex.next(), Token::Digit("2".to_string()));
+        assert_eq!(lex.next(), Token::Variable("a".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Eq));
+        assert_eq!(lex.next(), Token::Digit("1".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Plus));
+        assert_eq!(lex.next(), Token::Digit("2".into()));
+        assert_eq!(lex.next(), Token::EOF);
+    }
+
+    #[test]
+    fn test_regexp() {
+        let mut lex = Lex::new(r"/a\/[/]/gi.test(b) / c (/=/) x = /y/");
+        let regexp = |pattern: &'static str, flags: &'static str| Token::RegExp {
+            pattern: pattern.into(),
+            flags: flags.into(),
+        };
+
+        assert_eq!(lex.next(), regexp(r"a\/[/]", "gi"));
+        assert_eq!(lex.next(), Token::Punct(Punct::Dot));
+        assert_eq!(lex.next(), Token::Variable("test".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::LParen));
+        assert_eq!(lex.next(), Token::Variable("b".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::RParen));
+        assert_eq!(lex.next(), Token::Punct(Punct::Slash));
+        assert_eq!(lex.next(), Token::Variable("c".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::LParen));
+        assert_eq!(lex.next(), regexp("=", ""));
+        assert_eq!(lex.next(), Token::Punct(Punct::RParen));
+        lex.next();
+        lex.next();
+        assert_eq!(lex.next(), regexp("y", ""));
+    }
+
+    #[test]
+    fn test_division_after_keyword_property() {
+        let mut lex = Lex::new("a.default / 2 / 3; this?.new /b/ g");
+        assert_eq!(lex.next(), Token::Variable("a".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Dot));
+        assert_eq!(lex.next(), Token::Default);
+        assert_eq!(lex.next(), Token::Punct(Punct::Slash));
+        assert_eq!(lex.next(), Token::Digit("2".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Slash));
+        assert_eq!(lex.next(), Token::Digit("3".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Semicolon));
+        assert_eq!(lex.next(), Token::This);
+        assert_eq!(lex.next(), Token::Punct(Punct::QuestionDot));
+        assert_eq!(lex.next(), Token::New);
+        assert_eq!(lex.next(), Token::Punct(Punct::Slash));
+        assert_eq!(lex.next(), Token::Variable("b".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Slash));
+    }
+
+    #[test]
+    fn test_regexp_rescan() {
+        let mut lex = Lex::new("a) /b/g");
+        lex.next();
+        lex.next();
+        assert_eq!(lex.next(), Token::Punct(Punct::Slash));
+        assert_eq!(
+            lex.rescan_regexp(3),
+            SpannedToken {
+                token: Token::RegExp {
+                    pattern: "b".into(),
+                    flags: "g".into(),
+                },
+                span: Span::new(3, 7),
+                newline_before: false,
+            }
+        );
+        assert_eq!(lex.next(), Token::EOF);
+    }
+
+    #[test]
+    fn test_regexp_invalid() {
+        let error = |input| match Lex::new(input).next() {
+            Token::Error(e) => *e,
+            token => panic!("{token:?}"),
+        };
+
+        assert_eq!(
+            error("/a/gg"),
+            ParseError::InvalidRegExp {
+                reason: "duplicate flag",
+                span: Span::new(3, 5),
+            }
+        );
+        assert_eq!(
+            error("/a/x"),
+            ParseError::InvalidRegExp {
+                reason: "unknown flag",
+                span: Span::new(3, 4),
+            }
+        );
+        assert!(matches!(error("/a/uv"), ParseError::InvalidRegExp { .. }));
+        assert_eq!(
+            error("/a[/]\n/"),
+            ParseError::UnterminatedLiteral {
+                kind: "regular expression",
+                span: Span::new(0, 5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_identifier() {
+        let input = r"$ _a café π x\u0062c \u{1d400}\u200d l\u0065t";
+        let mut lex = Lex::new(input);
+
+        for name in ["$", "_a", "café", "π", "xbc", "\u{1d400}\u{200d}", "let"] {
+            assert_eq!(lex.next(), Token::Variable(name.into()));
+        }
+        assert_eq!(lex.next(), Token::EOF);
+    }
+
+    #[test]
+    fn test_identifier_invalid() {
+        let mut lex = Lex::new(r"\u0031a b\x41 \u0069f c");
+
+        assert_eq!(
+            lex.next(),
+            Token::Error(Box::new(ParseError::InvalidEscape {
+                reason: "invalid escape sequence in identifier",
+                span: Span::new(0, 6),
+            }))
+        );
+        assert!(matches!(lex.next(), Token::Error(_)));
+        assert_eq!(
+            lex.next(),
+            Token::Error(Box::new(ParseError::EscapedKeyword {
+                span: Span::new(14, 21),
+            }))
+        );
+        assert_eq!(lex.next(), Token::Variable("c".into()));
+        assert!(matches!(Lex::new("3π").next(), Token::Error(_)));
+    }
+
+    #[test]
+    fn test_punct() {
+        let input = "a=-1 a?.b x??y ...z[0]>>>=~c a?.5:1";
+        let mut lex = Lex::new(input);
+        let mut puncts = vec![];
+        loop {
+            match lex.next() {
+                Token::Punct(p) => puncts.push(p),
+                Token::EOF => break,
+                _ => {}
+            }
+        }
+        assert_eq!(
+            puncts,
+            vec![
+                Punct::Eq,
+                Punct::Minus,
+                Punct::QuestionDot,
+                Punct::QuestionQuestion,
+                Punct::Ellipsis,
+                Punct::LBracket,
+                Punct::RBracket,
+                Punct::UShrEq,
+                Punct::Tilde,
+                Punct::Question,
+                Punct::Colon,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_digit() {
+        let input = "1_000 1.5e-3 .5 1. 0x1F 0o17 0b1_0 10n 0xFFn 017 089 08.5 5E+2";
+        let mut lex = Lex::new(input);
+
+        for raw in input.split(' ') {
+            assert_eq!(lex.next(), Token::Digit(raw.into()));
+        }
+        assert_eq!(lex.next(), Token::EOF);
+        assert_eq!(lex.legacy_octal, vec![45, 49, 53]);
+    }
+
+    #[test]
+    fn test_digit_dot() {
+        let mut lex = Lex::new("1..a");
+
+        assert_eq!(lex.next(), Token::Digit("1.".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Dot));
+        assert_eq!(lex.next(), Token::Variable("a".into()));
+    }
+
+    #[test]
+    fn test_digit_invalid() {
+        for input in [
+            "1__0", "1_", "0_1", "0x", "0x_1", "1._5", "1e", "1.5n", "1e3n", "017n", "3in", "0b12",
+        ] {
+            let source = format!("{input};");
+            let mut lex = Lex::new(&source);
+            let token = lex.next();
+            assert!(
+                matches!(&token, Token::Error(e) if matches!(**e, ParseError::InvalidNumber { .. })),
+                "{input} should not lex, got {token:?}"
+            );
+            assert_eq!(lex.next(), Token::Punct(Punct::Semicolon));
+        }
+    }
+
+    #[test]
+    fn test_unexpected_character() {
+        let mut lex = Lex::new("a @ b");
+
+        assert_eq!(lex.next(), Token::Variable("a".into()));
+        assert_eq!(
+            lex.next(),
+            Token::Error(Box::new(ParseError::UnexpectedCharacter {
+                found: '@',
+                span: Span::new(2, 3),
+            }))
+        );
+        assert_eq!(lex.next(), Token::Variable("b".into()));
+        assert_eq!(lex.next(), Token::EOF);
+        assert_eq!(lex.next(), Token::EOF);
+    }
+
+    #[test]
+    fn test_comment() {
+        let input = "#!/usr/bin/env node\na /* b */ +// c\n/**/d";
+        let mut lex = Lex::new(input);
+
+        assert_eq!(lex.next(), Token::Variable("a".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::Plus));
+        assert_eq!(lex.next(), Token::Variable("d".into()));
         assert_eq!(lex.next(), Token::EOF);
+        assert_eq!(
+            lex.comments,
+            vec![
+                Comment {
+                    kind: CommentKind::Hashbang,
+                    text: "/usr/bin/env node",
+                    span: Span::new(0, 19),
+                },
+                Comment {
+                    kind: CommentKind::Block,
+                    text: " b ",
+                    span: Span::new(22, 29),
+                },
+                Comment {
+                    kind: CommentKind::Line,
+                    text: " c",
+                    span: Span::new(31, 35),
+                },
+                Comment {
+                    kind: CommentKind::Block,
+                    text: "",
+                    span: Span::new(36, 40),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_span() {
+        let mut lex = Lex::new("let a =\n  'é' // π\n+ 1");
+
+        let spans: Vec<Span> = (0..6).map(|_| lex.next_token().span).collect();
+        assert_eq!(
+            spans,
+            vec![
+                Span::new(0, 3),
+                Span::new(4, 5),
+                Span::new(6, 7),
+                Span::new(10, 14),
+                Span::new(21, 22),
+                Span::new(23, 24),
+            ]
+        );
+        assert_eq!(lex.comments[0].span, Span::new(15, 20));
+        assert_eq!(lex.line_col(10), (2, 3));
+        assert_eq!(lex.line_col(23), (3, 3));
+    }
+
+    #[test]
+    fn test_borrowed() {
+        let mut lex = Lex::new(r"abc 'd' 'e\n' `f${");
+
+        assert!(matches!(lex.next(), Token::Variable(Cow::Borrowed("abc"))));
+        assert!(matches!(lex.next(), Token::String(Cow::Borrowed("d"))));
+        assert!(matches!(lex.next(), Token::String(Cow::Owned(_))));
+        assert!(matches!(
+            lex.next(),
+            Token::Template {
+                cooked: Some(Cow::Borrowed("f")),
+                raw: Cow::Borrowed("f"),
+                tail: false,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_newline_before() {
+        let mut lex = Lex::new("a\tb\n c /*\n*/ d /**/ e // f\ng\u{2028}h");
+        let flags: Vec<bool> = (0..8).map(|_| lex.next_token().newline_before).collect();
+        assert_eq!(
+            flags,
+            vec![false, false, true, true, false, true, true, false]
+        );
+    }
+
+    #[test]
+    fn test_string() {
+        let input = r#"'a"b' "c'd" 'e\'f' "g\\h""#;
+        let mut lex = Lex::new(input);
+
+        assert_eq!(lex.next(), Token::String("a\"b".into()));
+        assert_eq!(lex.next(), Token::String("c'd".into()));
+        assert_eq!(lex.next(), Token::String("e'f".into()));
+        assert_eq!(lex.next(), Token::String("g\\h".into()));
+        assert_eq!(lex.next(), Token::EOF);
+    }
+
+    #[test]
+    fn test_string_escape() {
+        let input = r#"'\n\t\x41\u0042\u{43}\u{1F600}\uD83D\uDE00\0' 'a\
+b' 'x\z'"#;
+        let mut lex = Lex::new(input);
+
+        assert_eq!(
+            lex.next(),
+            Token::String("\n\tABC\u{1F600}\u{1F600}\0".into())
+        );
+        assert_eq!(lex.next(), Token::String("ab".into()));
+        assert_eq!(lex.next(), Token::String("xz".into()));
+        assert!(lex.legacy_octal.is_empty());
+    }
+
+    #[test]
+    fn test_string_legacy_octal() {
+        let mut lex = Lex::new(r#"'\101\08\8'"#);
+
+        assert_eq!(lex.next(), Token::String("A\u{0}88".into()));
+        assert_eq!(lex.legacy_octal, vec![1, 5, 8]);
+    }
+
+    #[test]
+    fn test_template() {
+        let mut lex = Lex::new("`a\\n${b}c\r\n` `\\unicode` `\\u{`");
+
+        assert_eq!(
+            lex.next(),
+            Token::Template {
+                cooked: Some("a\n".into()),
+                raw: "a\\n".into(),
+                tail: false,
+            }
+        );
+        assert_eq!(lex.next(), Token::Variable("b".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::RBrace));
+        let pos = lex.pos();
+        assert_eq!(
+            lex.rescan_template(pos),
+            SpannedToken {
+                token: Token::Template {
+                    cooked: Some("c\n".into()),
+                    raw: "c\n".into(),
+                    tail: true,
+                },
+                span: Span::new(7, 12),
+                newline_before: false,
+            }
+        );
+        assert_eq!(
+            lex.next(),
+            Token::Template {
+                cooked: None,
+                raw: "\\unicode".into(),
+                tail: true,
+            }
+        );
+        // the closing backtick after an invalid escape still ends the template
+        assert_eq!(
+            lex.next(),
+            Token::Template {
+                cooked: None,
+                raw: "\\u{".into(),
+                tail: true,
+            }
+        );
+        assert_eq!(lex.next(), Token::EOF);
+    }
+
+    #[test]
+    fn test_string_unterminated() {
+        let mut lex = Lex::new("'abc\n'x'");
+
+        assert_eq!(
+            lex.next(),
+            Token::Error(Box::new(ParseError::UnterminatedLiteral {
+                kind: "string",
+                span: Span::new(0, 4),
+            }))
+        );
+        assert_eq!(lex.next(), Token::String("x".into()));
+    }
+
+    #[test]
+    fn test_invalid_escape() {
+        let mut lex = Lex::new(r"'\x4g' + `${a}");
+
+        assert_eq!(
+            lex.next(),
+            Token::Error(Box::new(ParseError::InvalidEscape {
+                reason: "invalid hexadecimal escape sequence",
+                span: Span::new(1, 4),
+            }))
+        );
+        assert_eq!(lex.next(), Token::Punct(Punct::Plus));
+        assert!(matches!(lex.next(), Token::Template { .. }));
+        assert_eq!(lex.next(), Token::Variable("a".into()));
+        assert_eq!(lex.next(), Token::Punct(Punct::RBrace));
+        let pos = lex.pos();
+        assert_eq!(
+            lex.rescan_template(pos).token,
+            Token::Error(Box::new(ParseError::UnterminatedLiteral {
+                kind: "template",
+                span: Span::new(13, 14),
+            }))
+        );
     }
 }
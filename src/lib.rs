@@ -0,0 +1,13 @@
+// the AST keeps its children as `Vec<Box<Node>>`, like the nodes themselves
+#![allow(clippy::vec_box)]
+pub mod error;
+pub mod exp;
+pub mod express;
+mod express_test;
+pub mod lex;
+pub mod node;
+pub mod parser;
+pub mod punct;
+pub mod scope;
+pub mod span;
+pub mod unicode;
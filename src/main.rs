@@ -1,11 +1,3 @@
-#![allow(warnings)]
-mod express;
-mod lex;
-mod exp;
-mod parser;
-mod express_test;
-mod node;
-
 fn main() {
 
 }
\ No newline at end of file
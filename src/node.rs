@@ -1,59 +1,416 @@
 use crate::lex::Token;
+use crate::span::Span;
 
 #[derive(Debug, PartialEq)]
 pub enum Node {
-    EmptyStatement {},
+    EmptyStatement {
+        span: Span,
+    },
+    BlockStatement {
+        body: Vec<Box<Node>>,
+        span: Span,
+    },
+    ExpressionStatement {
+        expression: Box<Node>,
+        span: Span,
+    },
     Identity {
         name: String,
+        span: Span,
     },
     NumericLiteral {
+        value: NumericValue,
+        raw: String,
+        span: Span,
+    },
+    StringLiteral {
         value: String,
+        span: Span,
+    },
+    BooleanLiteral {
+        value: bool,
+        span: Span,
+    },
+    NullLiteral {
+        span: Span,
+    },
+    ThisExpression {
+        span: Span,
+    },
+    RegExpLiteral {
+        pattern: String,
+        flags: String,
+        span: Span,
+    },
+    TemplateLiteral {
+        quasis: Vec<Box<Node>>,
+        expressions: Vec<Box<Node>>,
+        span: Span,
+    },
+    TemplateElement {
+        raw: String,
+        cooked: Option<String>,
+        tail: bool,
+        span: Span,
+    },
+    TaggedTemplateExpression {
+        tag: Box<Node>,
+        quasi: Box<Node>,
+        span: Span,
     },
     VariableDeclaration {
-        kind: Token,
+        kind: Token<'static>,
         declarations: Vec<Box<Node>>,
+        span: Span,
     },
     VariableDeclarator {
         id: Box<Node>,
         init: Box<Node>,
+        span: Span,
     },
     AssignmentExpression {
         left: Box<Node>,
         operator: String,
         right: Box<Node>,
+        span: Span,
     },
     BinaryExpression {
         left: Box<Node>,
         operator: String,
         right: Box<Node>,
+        span: Span,
     },
     UnaryExpression {
         operator: String,
         prefix: bool,
         argument: Box<Node>,
+        span: Span,
     },
     UpdateExpression {
         operator: String,
         prefix: bool,
         argument: Box<Node>,
+        span: Span,
     },
     MemberExpression {
         object: Box<Node>,
         property: Box<Node>,
+        /// `a[b]` rather than `a.b`
+        computed: bool,
+        /// `a?.b`
+        optional: bool,
+        span: Span,
     },
     ConditionalExpression {
         test: Box<Node>,
         consequent: Box<Node>,
         alternate: Box<Node>,
+        span: Span,
     },
     CallExpression {
         callee: Box<Node>,
         arguments: Vec<Box<Node>>,
+        /// `a?.()`
+        optional: bool,
+        span: Span,
+    },
+    /// an optional chain like `a?.b.c`, which short-circuits as a whole
+    ChainExpression {
+        expression: Box<Node>,
+        span: Span,
+    },
+    LogicalExpression {
+        operator: String,
+        left: Box<Node>,
+        right: Box<Node>,
+        span: Span,
+    },
+    IfStatement {
+        test: Box<Node>,
+        consequent: Box<Node>,
+        alternate: Option<Box<Node>>,
+        span: Span,
+    },
+    WhileStatement {
+        test: Box<Node>,
+        body: Box<Node>,
+        span: Span,
+    },
+    DoWhileStatement {
+        body: Box<Node>,
+        test: Box<Node>,
+        span: Span,
+    },
+    SwitchStatement {
+        discriminant: Box<Node>,
+        cases: Vec<Box<Node>>,
+        span: Span,
+    },
+    SwitchCase {
+        /// `None` for `default:`
+        test: Option<Box<Node>>,
+        consequent: Vec<Box<Node>>,
+        span: Span,
+    },
+    BreakStatement {
+        label: Option<Box<Node>>,
+        span: Span,
+    },
+    ContinueStatement {
+        label: Option<Box<Node>>,
+        span: Span,
+    },
+    LabeledStatement {
+        label: Box<Node>,
+        body: Box<Node>,
+        span: Span,
+    },
+    ArrayPattern {
+        /// `None` for holes, like in `[, a]`
+        elements: Vec<Option<Box<Node>>>,
+        span: Span,
+    },
+    ArrayExpression {
+        /// `None` for holes, like in `[1, , 3]`
+        elements: Vec<Option<Box<Node>>>,
+        /// whether a `,` follows the last element, like in `[a, ...b,]`, which a rest
+        /// element may not have when the array is reinterpreted as a pattern
+        trailing_comma: bool,
+        span: Span,
+    },
+    ObjectExpression {
+        properties: Vec<Box<Node>>,
+        span: Span,
+    },
+    Property {
+        key: Box<Node>,
+        value: Box<Node>,
+        /// "init", "get" or "set"
+        kind: String,
+        computed: bool,
+        shorthand: bool,
+        method: bool,
+        span: Span,
+    },
+    SpreadElement {
+        argument: Box<Node>,
+        span: Span,
+    },
+    AssignmentPattern {
+        left: Box<Node>,
+        right: Box<Node>,
+        span: Span,
+    },
+    RestElement {
+        argument: Box<Node>,
+        span: Span,
+    },
+    FunctionDeclaration {
+        id: Box<Node>,
+        params: Vec<Box<Node>>,
+        body: Box<Node>,
+        is_async: bool,
+        is_generator: bool,
+        span: Span,
+    },
+    FunctionExpression {
+        id: Option<Box<Node>>,
+        params: Vec<Box<Node>>,
+        body: Box<Node>,
+        is_async: bool,
+        is_generator: bool,
+        span: Span,
+    },
+    ArrowFunctionExpression {
+        params: Vec<Box<Node>>,
+        body: Box<Node>,
+        /// whether the body is an expression rather than a block, like in `x => x`
+        expression: bool,
+        is_async: bool,
+        span: Span,
+    },
+    AwaitExpression {
+        argument: Box<Node>,
+        span: Span,
+    },
+    YieldExpression {
+        /// `None` for a bare `yield`
+        argument: Option<Box<Node>>,
+        /// whether it is `yield*`, which yields every value of its argument
+        delegate: bool,
+        span: Span,
+    },
+    SequenceExpression {
+        expressions: Vec<Box<Node>>,
+        span: Span,
+    },
+    ReturnStatement {
+        argument: Option<Box<Node>>,
+        span: Span,
     },
     ForStatement {
         init: Box<Node>,
         test: Box<Node>,
         update: Box<Node>,
-        body: Box<Vec<Box<Node>>>,
+        body: Box<Node>,
+        span: Span,
+    },
+    ForInStatement {
+        left: Box<Node>,
+        right: Box<Node>,
+        body: Box<Node>,
+        span: Span,
+    },
+    ForOfStatement {
+        left: Box<Node>,
+        right: Box<Node>,
+        body: Box<Node>,
+        /// `for await (... of ...)`
+        is_await: bool,
+        span: Span,
     },
 }
+
+impl Node {
+    pub fn span(&self) -> Span {
+        match self {
+            Node::EmptyStatement { span, .. } => *span,
+            Node::BlockStatement { span, .. } => *span,
+            Node::ExpressionStatement { span, .. } => *span,
+            Node::Identity { span, .. } => *span,
+            Node::NumericLiteral { span, .. } => *span,
+            Node::StringLiteral { span, .. } => *span,
+            Node::BooleanLiteral { span, .. } => *span,
+            Node::NullLiteral { span } => *span,
+            Node::ThisExpression { span } => *span,
+            Node::RegExpLiteral { span, .. } => *span,
+            Node::TemplateLiteral { span, .. } => *span,
+            Node::TemplateElement { span, .. } => *span,
+            Node::TaggedTemplateExpression { span, .. } => *span,
+            Node::VariableDeclaration { span, .. } => *span,
+            Node::VariableDeclarator { span, .. } => *span,
+            Node::AssignmentExpression { span, .. } => *span,
+            Node::BinaryExpression { span, .. } => *span,
+            Node::UnaryExpression { span, .. } => *span,
+            Node::UpdateExpression { span, .. } => *span,
+            Node::MemberExpression { span, .. } => *span,
+            Node::ConditionalExpression { span, .. } => *span,
+            Node::CallExpression { span, .. } => *span,
+            Node::ChainExpression { span, .. } => *span,
+            Node::LogicalExpression { span, .. } => *span,
+            Node::IfStatement { span, .. } => *span,
+            Node::WhileStatement { span, .. } => *span,
+            Node::DoWhileStatement { span, .. } => *span,
+            Node::SwitchStatement { span, .. } => *span,
+            Node::SwitchCase { span, .. } => *span,
+            Node::BreakStatement { span, .. } => *span,
+            Node::ContinueStatement { span, .. } => *span,
+            Node::LabeledStatement { span, .. } => *span,
+            Node::ArrayPattern { span, .. } => *span,
+            Node::ArrayExpression { span, .. } => *span,
+            Node::ObjectExpression { span, .. } => *span,
+            Node::Property { span, .. } => *span,
+            Node::SpreadElement { span, .. } => *span,
+            Node::AssignmentPattern { span, .. } => *span,
+            Node::RestElement { span, .. } => *span,
+            Node::FunctionDeclaration { span, .. } => *span,
+            Node::FunctionExpression { span, .. } => *span,
+            Node::ArrowFunctionExpression { span, .. } => *span,
+            Node::AwaitExpression { span, .. } => *span,
+            Node::YieldExpression { span, .. } => *span,
+            Node::SequenceExpression { span, .. } => *span,
+            Node::ReturnStatement { span, .. } => *span,
+            Node::ForStatement { span, .. } => *span,
+            Node::ForInStatement { span, .. } => *span,
+            Node::ForOfStatement { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum NumericValue {
+    Number(f64),
+    /// decimal digits of the BigInt value
+    BigInt(String),
+}
+
+impl NumericValue {
+    /// evaluates the raw text of a numeric literal accepted by the lexer
+    pub fn parse(raw: &str) -> NumericValue {
+        let text = raw.replace('_', "");
+        let (text, bigint) = match text.strip_suffix('n') {
+            Some(text) => (text, true),
+            None => (text.as_str(), false),
+        };
+        let prefix = text.get(..2).unwrap_or("").to_ascii_lowercase();
+        let (radix, digits) = match prefix.as_str() {
+            "0x" => (16, &text[2..]),
+            "0o" => (8, &text[2..]),
+            "0b" => (2, &text[2..]),
+            _ if text.len() > 1 && text.starts_with('0') && text.chars().all(|c| c.is_digit(8)) => {
+                (8, &text[1..])
+            }
+            _ => (10, text),
+        };
+        if bigint {
+            return NumericValue::BigInt(to_decimal(digits, radix));
+        }
+        if radix == 10 {
+            return NumericValue::Number(digits.parse().unwrap());
+        }
+        let value = match u128::from_str_radix(digits, radix) {
+            Ok(value) => value as f64,
+            Err(_) => digits.chars().fold(0.0, |value, c| {
+                value * radix as f64 + c.to_digit(radix).unwrap() as f64
+            }),
+        };
+        NumericValue::Number(value)
+    }
+}
+
+fn to_decimal(digits: &str, radix: u32) -> String {
+    const BASE: u64 = 1_000_000_000;
+    // little endian limbs in base 10^9
+    let mut limbs: Vec<u64> = vec![0];
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix).unwrap() as u64;
+        for limb in limbs.iter_mut() {
+            let value = *limb * radix as u64 + carry;
+            *limb = value % BASE;
+            carry = value / BASE;
+        }
+        if carry > 0 {
+            limbs.push(carry);
+        }
+    }
+    let mut text = limbs.last().unwrap().to_string();
+    for limb in limbs.iter().rev().skip(1) {
+        text.push_str(&format!("{limb:09}"));
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::NumericValue::{self, BigInt, Number};
+
+    #[test]
+    fn test_numeric_value() {
+        assert_eq!(NumericValue::parse("1_000"), Number(1000.0));
+        assert_eq!(NumericValue::parse("1.5e-3"), Number(0.0015));
+        assert_eq!(NumericValue::parse(".5"), Number(0.5));
+        assert_eq!(NumericValue::parse("1."), Number(1.0));
+        assert_eq!(NumericValue::parse("0X1f"), Number(31.0));
+        assert_eq!(NumericValue::parse("0o17"), Number(15.0));
+        assert_eq!(NumericValue::parse("0b1_0"), Number(2.0));
+        assert_eq!(NumericValue::parse("017"), Number(15.0));
+        assert_eq!(NumericValue::parse("089"), Number(89.0));
+        assert_eq!(NumericValue::parse("0"), Number(0.0));
+        assert_eq!(NumericValue::parse("0n"), BigInt("0".to_string()));
+        assert_eq!(NumericValue::parse("1_0n"), BigInt("10".to_string()));
+        assert_eq!(
+            NumericValue::parse("0xFFFFFFFFFFFFFFFFFFFFn"),
+            BigInt("1208925819614629174706175".to_string())
+        );
+    }
+}
@@ -1,68 +1,378 @@
-use crate::exp::declaration_exp::build_let;
-use crate::exp::for_exp::build_for;
-use crate::express::{parse_expression};
-use crate::lex::{Lex, Token};
+use crate::error::ParseError;
+use crate::exp::statement_exp::parse_statement_list;
+use crate::lex::{Comment, Lex, SpannedToken, Token};
 use crate::node::Node;
+use crate::punct::Punct;
+use crate::scope::ScopeStack;
+use crate::span::Span;
 
-pub struct Parser {
-    pub current: Token,
-    pub lookahead: Token,
-    pub list: Vec<Token>,
-    lex: Lex,
+/// how deeply statements and expressions may nest, so that input like `((((...` fails with
+/// an error before it overflows the stack
+const MAX_DEPTH: usize = 200;
+
+pub struct Parser<'src> {
+    pub current: Token<'src>,
+    pub current_span: Span,
+    /// whether a line terminator precedes `current`
+    pub newline_before: bool,
+    /// byte offset where the previously consumed token ended
+    pub last_end: usize,
+    /// lexed on demand, so that the parser can still rescan `current` in another goal
+    lookahead: Option<(SpannedToken<'src>, usize)>,
+    pub list: Vec<Token<'src>>,
+    lex: Lex<'src>,
+    /// lex position right after `current`
+    current_end: usize,
+    /// labels of the enclosing labeled statements, innermost last
+    pub labels: Vec<Label>,
+    /// how many loops enclose the current statement
+    pub loop_depth: usize,
+    /// how many `switch` statements enclose the current statement
+    pub switch_depth: usize,
+    /// whether the head of a `for` is being parsed, where `in` and `of` end the
+    /// expression instead of being operators
+    pub in_for_init: bool,
+    pub scopes: ScopeStack,
+    /// whether the current statement is inside a function body, where `return` is allowed
+    pub in_function: bool,
+    /// whether the current function is async, where `await` is an operator
+    pub in_async: bool,
+    /// whether the current function is a generator, where `yield` is an operator
+    pub in_generator: bool,
+    /// how many statements and expressions enclose the current one
+    depth: usize,
+}
+
+pub struct Label {
+    pub name: String,
+    /// whether the labeled statement is a loop, so that `continue` may target it
+    pub is_loop: bool,
+    /// where the labeled statement starts, shared by every label in `a: b: while ...`
+    pub statement_start: usize,
 }
 
-impl Parser {
-    pub fn new(input: String) -> Parser {
-        let mut lex = Lex::new(input.to_string());
-        let current = lex.next();
-        let parser = Parser {
-            current: current.clone(),
-            lookahead: lex.next(),
-            list: vec![current],
+impl<'src> Parser<'src> {
+    pub fn new(input: &'src str) -> Parser<'src> {
+        let mut lex = Lex::new(input);
+        let current = lex.next_token();
+        Parser {
+            current: current.token.clone(),
+            current_span: current.span,
+            newline_before: current.newline_before,
+            last_end: 0,
+            lookahead: None,
+            list: vec![current.token],
+            current_end: lex.pos(),
             lex,
+            labels: vec![],
+            loop_depth: 0,
+            switch_depth: 0,
+            in_for_init: false,
+            scopes: ScopeStack::new(),
+            in_function: false,
+            in_async: false,
+            in_generator: false,
+            depth: 0,
+        }
+    }
+
+    pub fn next(&mut self) {
+        let (current, end) = match self.lookahead.take() {
+            Some(lookahead) => lookahead,
+            None => (self.lex.next_token(), self.lex.pos()),
         };
+        self.last_end = self.current_span.end;
+        self.current = current.token;
+        self.current_span = current.span;
+        self.newline_before = current.newline_before;
+        self.current_end = end;
+        self.list.push(self.current.clone());
+    }
 
-        parser
+    pub fn peek(&mut self) -> &Token<'src> {
+        if self.lookahead.is_none() {
+            let token = self.lex.next_token();
+            self.lookahead = Some((token, self.lex.pos()));
+        }
+        &self.lookahead.as_ref().unwrap().0.token
     }
 
-    pub fn next(&mut self) {
-        self.current = self.lookahead.clone();
+    /// whether a line terminator precedes the token after `current`
+    pub fn peek_newline_before(&mut self) -> bool {
+        self.peek();
+        self.lookahead.as_ref().unwrap().0.newline_before
+    }
+
+    /// turns the `}` closing a template substitution into the template piece that follows it
+    pub fn rescan_template(&mut self) {
+        self.lookahead = None;
+        let current = self.lex.rescan_template(self.current_end);
+        self.current = current.token;
+        self.current_span = current.span;
+        self.current_end = self.lex.pos();
+        self.list.pop();
         self.list.push(self.current.clone());
-        self.lookahead = self.lex.next();
     }
 
-    pub fn parse(&mut self) -> Result<Vec<Box<Node>>, String> {
-        let mut ast = vec![];
-        loop {
-            match self.current {
-                Token::EOF => break,
-                Token::Var | Token::Let | Token::Const => {
-                    ast.push(build_let(self)?);
-                }
-                Token::For => {
-                    ast.push(build_for(self)?);
-                }
+    /// turns a `/` or `/=` where an expression is expected into a regular expression
+    pub fn rescan_regexp(&mut self) {
+        self.lookahead = None;
+        let current = self.lex.rescan_regexp(self.current_span.start);
+        self.current = current.token;
+        self.current_span = current.span;
+        self.current_end = self.lex.pos();
+        self.list.pop();
+        self.list.push(self.current.clone());
+    }
 
-                _ => {
-                    ast.push(parse_expression(self, 1)?);
-                }
-            }
+    /// runs `parse` one nesting level deeper, failing once the input nests too deeply
+    pub fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ParseError::NestingTooDeep {
+                span: self.current_span,
+            });
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    /// the span from `start` to the end of the last consumed token
+    pub fn span_from(&self, start: usize) -> Span {
+        Span::new(start, self.last_end)
+    }
+
+    /// the 1-based line and column of a byte offset
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        self.lex.line_col(offset)
+    }
+
+    /// an error for the current token, which is none of `expected`
+    pub fn unexpected(&self, expected: &[&str]) -> ParseError {
+        if let Token::Error(e) = &self.current {
+            // the lexer already explained what is wrong with this token
+            return (**e).clone();
+        }
+        ParseError::UnexpectedToken {
+            found: self.current.clone().into_owned(),
+            expected: expected.iter().map(|s| s.to_string()).collect(),
+            span: self.current_span,
+        }
+    }
+
+    /// whether automatic semicolon insertion may end a statement before the current
+    /// token, which is `}`, the end of input, or on a new line
+    pub fn can_insert_semicolon(&self) -> bool {
+        self.newline_before || matches!(self.current, Token::Punct(Punct::RBrace) | Token::EOF)
+    }
+
+    /// consumes the `;` that ends a statement, or inserts one where the rules allow it
+    pub fn consume_semicolon(&mut self) -> Result<(), ParseError> {
+        if self.current == Token::Punct(Punct::Semicolon) {
+            self.next();
+            return Ok(());
+        }
+        if self.can_insert_semicolon() {
+            return Ok(());
+        }
+        Err(self.unexpected(&["`;`"]))
+    }
+
+    /// whether the current `let` starts a declaration, rather than being an identifier
+    /// as in `let = 1`
+    pub fn at_let_declaration(&mut self) -> bool {
+        matches!(
+            self.peek(),
+            Token::Variable(_)
+                | Token::Let
+                | Token::Async
+                | Token::Await
+                | Token::Yield
+                | Token::Punct(Punct::LBracket | Punct::LBrace)
+        )
+    }
+
+    /// parses the whole input, returning its statements and the comments skipped between them
+    pub fn parse(&mut self) -> Result<(Vec<Box<Node>>, Vec<Comment<'src>>), ParseError> {
+        let ast = parse_statement_list(self)?;
+        if self.current != Token::EOF {
+            return Err(self.unexpected(&["statement"]));
         }
-        Ok(ast)
+        Ok((ast, std::mem::take(&mut self.lex.comments)))
     }
 }
 
 #[cfg(test)]
 mod parser_test {
-    use crate::lex::Token;
+    use crate::error::ParseError;
+    use crate::lex::{Lex, Token};
     use crate::parser::Parser;
+    use crate::punct::Punct;
+    use crate::span::Span;
 
     #[test]
     fn test1() {
-        let mut parser = Parser::new(" \n let \n a \n = \n b\n ".to_string());
+        let mut parser = Parser::new(" \n let \n a \n = \n b\n ");
 
         assert_eq!(Token::Let, parser.current);
         parser.next();
-        assert_eq!(Token::Variable("a".to_string()), parser.current);
+        assert_eq!(Token::Variable("a".into()), parser.current);
+    }
+
+    #[test]
+    fn test_asi() {
+        let statements = |input| {
+            let mut parser = Parser::new(input);
+            let (ast, _) = parser.parse().unwrap();
+            ast.iter()
+                .map(|node| (node.span().start, node.span().end))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(statements("let a = 1\nlet b = 2"), vec![(0, 9), (10, 19)]);
+        // `++` may not follow its operand on another line
+        assert_eq!(statements("a\n++b"), vec![(0, 1), (2, 5)]);
+        // but a line break alone does not end the statement
+        assert_eq!(statements("a = b\n(c)"), vec![(0, 9)]);
+        assert_eq!(
+            statements("a /* \n */ b; c"),
+            vec![(0, 1), (10, 12), (13, 14)]
+        );
+        assert_eq!(statements("a\u{2028}b"), vec![(0, 1), (4, 5)]);
+    }
+
+    #[test]
+    fn test_asi_error() {
+        let mut parser = Parser::new("let a = 1 let b = 2");
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::UnexpectedToken {
+                found: Token::Let,
+                expected: vec!["`;`".to_string()],
+                span: Span::new(10, 13),
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_location() {
+        let mut parser = Parser::new("a = 1\n  + (b;");
+
+        let error = parser.parse().unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::UnexpectedToken {
+                found: Token::Punct(Punct::Semicolon),
+                expected: vec!["`)`".to_string()],
+                span: Span::new(12, 13),
+            }
+        );
+        assert_eq!(parser.line_col(error.span().start), (2, 7));
+    }
+
+    #[test]
+    fn test_lex_error() {
+        let mut parser = Parser::new("a = 'b\n");
+
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::UnterminatedLiteral {
+                kind: "string",
+                span: Span::new(4, 6),
+            })
+        );
+    }
+
+    /// xorshift, so the fuzz inputs are the same on every run
+    fn random(seed: &mut u64) -> u64 {
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+        *seed
+    }
+
+    #[test]
+    fn test_fuzz_never_panics() {
+        let alphabet: Vec<char> =
+            "aZ_$09.eExXnb+-*/%=<>!?:;,(){}[]'\"`\\ \t\r\n#@~^&|\u{0}é€😀\u{2028}"
+                .chars()
+                .collect();
+        let fragments = [
+            "${", "/*", "*/", "//", "0x", "1_0", "\\u{", "for(", "let ", "`a${",
+        ];
+        let mut seed = 0x2545F4914F6CDD1D;
+        for _ in 0..3000 {
+            let mut input = String::new();
+            let len = random(&mut seed) % 24;
+            for _ in 0..len {
+                let n = random(&mut seed) as usize;
+                if n.is_multiple_of(4) {
+                    input.push_str(fragments[n / 4 % fragments.len()]);
+                } else {
+                    input.push(alphabet[n / 4 % alphabet.len()]);
+                }
+            }
+
+            let result = std::panic::catch_unwind(|| {
+                let mut lex = Lex::new(&input);
+                for _ in 0..=input.len() {
+                    if lex.next() == Token::EOF {
+                        return;
+                    }
+                }
+                panic!("lexer did not reach EOF");
+            });
+            assert!(result.is_ok(), "lexing {input:?} panicked");
+            let result = std::panic::catch_unwind(|| Parser::new(&input).parse().map(|_| ()));
+            assert!(result.is_ok(), "parsing {input:?} panicked");
+        }
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let deep = |open: &str, middle: &str, close: &str| {
+            format!("{}{middle}{}", open.repeat(100_000), close.repeat(100_000))
+        };
+        for input in [
+            deep("(", "a", ")"),
+            deep("[", "a", "]"),
+            deep("-", "a", ""),
+            deep("a = ", "b", ""),
+            deep("() => ", "a", ""),
+            deep("`${", "a", "}`"),
+            format!("x = {}", deep("{a:", "b", "}")),
+            deep("{", "", "}"),
+            deep("if (a) ", "b", ""),
+            deep("function f() {", "", "}"),
+        ] {
+            assert!(
+                matches!(
+                    Parser::new(&input).parse(),
+                    Err(ParseError::NestingTooDeep { .. })
+                ),
+                "parsing {:?}... did not fail",
+                &input[..20]
+            );
+        }
+
+        // well below the limit everything parses
+        let input = format!("{}a{}", "(".repeat(150), ")".repeat(150));
+        Parser::new(&input).parse().unwrap();
+    }
+
+    #[test]
+    fn test_comments() {
+        let mut parser = Parser::new("a /* b */ + c // d");
+        let (ast, comments) = parser.parse().unwrap();
+
+        assert_eq!(ast.len(), 1);
+        assert_eq!(
+            comments.iter().map(|c| c.text).collect::<Vec<_>>(),
+            vec![" b ", " d"]
+        );
     }
 }
@@ -0,0 +1,242 @@
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+/// an ECMAScript punctuator
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Punct {
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    Ellipsis,
+    Semicolon,
+    Comma,
+    Lt,
+    Gt,
+    LtEq,
+    GtEq,
+    EqEq,
+    NotEq,
+    EqEqEq,
+    NotEqEq,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    StarStar,
+    PlusPlus,
+    MinusMinus,
+    Shl,
+    Shr,
+    UShr,
+    Amp,
+    Pipe,
+    Caret,
+    Bang,
+    Tilde,
+    AmpAmp,
+    PipePipe,
+    QuestionQuestion,
+    Question,
+    QuestionDot,
+    Colon,
+    Eq,
+    PlusEq,
+    MinusEq,
+    StarEq,
+    SlashEq,
+    PercentEq,
+    StarStarEq,
+    ShlEq,
+    ShrEq,
+    UShrEq,
+    AmpEq,
+    PipeEq,
+    CaretEq,
+    AmpAmpEq,
+    PipePipeEq,
+    QuestionQuestionEq,
+    Arrow,
+}
+
+const PUNCTUATORS: [(&str, Punct); 57] = [
+    ("{", Punct::LBrace),
+    ("}", Punct::RBrace),
+    ("(", Punct::LParen),
+    (")", Punct::RParen),
+    ("[", Punct::LBracket),
+    ("]", Punct::RBracket),
+    (".", Punct::Dot),
+    ("...", Punct::Ellipsis),
+    (";", Punct::Semicolon),
+    (",", Punct::Comma),
+    ("<", Punct::Lt),
+    (">", Punct::Gt),
+    ("<=", Punct::LtEq),
+    (">=", Punct::GtEq),
+    ("==", Punct::EqEq),
+    ("!=", Punct::NotEq),
+    ("===", Punct::EqEqEq),
+    ("!==", Punct::NotEqEq),
+    ("+", Punct::Plus),
+    ("-", Punct::Minus),
+    ("*", Punct::Star),
+    ("/", Punct::Slash),
+    ("%", Punct::Percent),
+    ("**", Punct::StarStar),
+    ("++", Punct::PlusPlus),
+    ("--", Punct::MinusMinus),
+    ("<<", Punct::Shl),
+    (">>", Punct::Shr),
+    (">>>", Punct::UShr),
+    ("&", Punct::Amp),
+    ("|", Punct::Pipe),
+    ("^", Punct::Caret),
+    ("!", Punct::Bang),
+    ("~", Punct::Tilde),
+    ("&&", Punct::AmpAmp),
+    ("||", Punct::PipePipe),
+    ("??", Punct::QuestionQuestion),
+    ("?", Punct::Question),
+    ("?.", Punct::QuestionDot),
+    (":", Punct::Colon),
+    ("=", Punct::Eq),
+    ("+=", Punct::PlusEq),
+    ("-=", Punct::MinusEq),
+    ("*=", Punct::StarEq),
+    ("/=", Punct::SlashEq),
+    ("%=", Punct::PercentEq),
+    ("**=", Punct::StarStarEq),
+    ("<<=", Punct::ShlEq),
+    (">>=", Punct::ShrEq),
+    (">>>=", Punct::UShrEq),
+    ("&=", Punct::AmpEq),
+    ("|=", Punct::PipeEq),
+    ("^=", Punct::CaretEq),
+    ("&&=", Punct::AmpAmpEq),
+    ("||=", Punct::PipePipeEq),
+    ("??=", Punct::QuestionQuestionEq),
+    ("=>", Punct::Arrow),
+];
+
+impl Punct {
+    pub fn as_str(self) -> &'static str {
+        PUNCTUATORS
+            .iter()
+            .find(|(_, p)| *p == self)
+            .map(|(s, _)| *s)
+            .unwrap()
+    }
+
+    /// the longest punctuator at the start of `source` and its length in bytes
+    pub fn longest(source: &str) -> Option<(Punct, usize)> {
+        let trie = TRIE.get_or_init(Trie::new);
+        let bytes = source.as_bytes();
+        let mut node = 0;
+        let mut found = None;
+        for (i, b) in bytes.iter().enumerate() {
+            match trie.nodes[node].children.iter().find(|(c, _)| c == b) {
+                Some(&(_, child)) => node = child,
+                None => break,
+            }
+            if let Some(punct) = trie.nodes[node].punct {
+                found = Some((punct, i + 1));
+            }
+        }
+        // `a?.5:1` is a conditional, not an optional chain
+        if let Some((Punct::QuestionDot, _)) = found
+            && bytes.get(2).is_some_and(u8::is_ascii_digit)
+        {
+            found = Some((Punct::Question, 1));
+        }
+        found
+    }
+
+    /// whether this assigns, like `=` or `>>>=`
+    pub fn is_assignment(self) -> bool {
+        matches!(
+            self,
+            Punct::Eq
+                | Punct::PlusEq
+                | Punct::MinusEq
+                | Punct::StarEq
+                | Punct::SlashEq
+                | Punct::PercentEq
+                | Punct::StarStarEq
+                | Punct::ShlEq
+                | Punct::ShrEq
+                | Punct::UShrEq
+                | Punct::AmpEq
+                | Punct::PipeEq
+                | Punct::CaretEq
+                | Punct::AmpAmpEq
+                | Punct::PipePipeEq
+                | Punct::QuestionQuestionEq
+        )
+    }
+}
+
+impl Display for Punct {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+static TRIE: OnceLock<Trie> = OnceLock::new();
+
+/// a byte trie over `PUNCTUATORS`, walked for the longest match
+struct Trie {
+    nodes: Vec<TrieNode>,
+}
+
+#[derive(Default)]
+struct TrieNode {
+    children: Vec<(u8, usize)>,
+    /// the punctuator spelled by the path to this node
+    punct: Option<Punct>,
+}
+
+impl Trie {
+    fn new() -> Self {
+        let mut nodes = vec![TrieNode::default()];
+        for (text, punct) in PUNCTUATORS {
+            let mut node = 0;
+            for b in text.bytes() {
+                node = match nodes[node].children.iter().find(|(c, _)| *c == b) {
+                    Some(&(_, child)) => child,
+                    None => {
+                        nodes.push(TrieNode::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.push((b, child));
+                        child
+                    }
+                };
+            }
+            nodes[node].punct = Some(punct);
+        }
+        Trie { nodes }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::punct::{PUNCTUATORS, Punct};
+
+    #[test]
+    fn test_longest() {
+        for (text, punct) in PUNCTUATORS {
+            assert_eq!(Punct::longest(text), Some((punct, text.len())), "{text}");
+            assert_eq!(punct.as_str(), text);
+        }
+        assert_eq!(Punct::longest("=-1"), Some((Punct::Eq, 1)));
+        assert_eq!(Punct::longest(">>>=>"), Some((Punct::UShrEq, 4)));
+        assert_eq!(Punct::longest("..a"), Some((Punct::Dot, 1)));
+        assert_eq!(Punct::longest("?.b"), Some((Punct::QuestionDot, 2)));
+        assert_eq!(Punct::longest("?.5"), Some((Punct::Question, 1)));
+        assert_eq!(Punct::longest("a"), None);
+    }
+}
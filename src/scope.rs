@@ -0,0 +1,102 @@
+use crate::error::ParseError;
+use crate::span::Span;
+use std::collections::HashSet;
+
+/// the names declared in the enclosing scopes, used to report redeclarations
+pub struct ScopeStack {
+    scopes: Vec<Scope>,
+}
+
+#[derive(Default)]
+struct Scope {
+    /// names declared with `let`, `const`, or a function in a block
+    lexical: HashSet<String>,
+    /// the names of `lexical` declared by functions, which may repeat each other
+    functions: HashSet<String>,
+    /// names declared with `var` here or in a nested block, since `var` hoists
+    var: HashSet<String>,
+    /// whether `var` declarations stop hoisting here
+    is_function: bool,
+}
+
+impl Default for ScopeStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScopeStack {
+    pub fn new() -> Self {
+        ScopeStack {
+            scopes: vec![Scope {
+                is_function: true,
+                ..Scope::default()
+            }],
+        }
+    }
+
+    pub fn enter(&mut self, is_function: bool) {
+        self.scopes.push(Scope {
+            is_function,
+            ..Scope::default()
+        });
+    }
+
+    pub fn exit(&mut self) {
+        self.scopes.pop();
+    }
+
+    /// declares `name` with `let` or `const` in the innermost scope
+    pub fn declare_lexical(&mut self, name: &str, span: Span) -> Result<(), ParseError> {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.lexical.contains(name) || scope.var.contains(name) {
+            return Err(redeclaration(name, span));
+        }
+        scope.lexical.insert(name.to_string());
+        Ok(())
+    }
+
+    /// declares a function `name` in the innermost scope, which is like `var` at the top of
+    /// a function or script and like `let` in a block, except that functions may repeat
+    pub fn declare_function(&mut self, name: &str, span: Span) -> Result<(), ParseError> {
+        let scope = self.scopes.last_mut().unwrap();
+        if scope.is_function {
+            if scope.lexical.contains(name) {
+                return Err(redeclaration(name, span));
+            }
+            scope.var.insert(name.to_string());
+            return Ok(());
+        }
+        if scope.functions.contains(name) {
+            return Ok(());
+        }
+        self.declare_lexical(name, span)?;
+        self.scopes
+            .last_mut()
+            .unwrap()
+            .functions
+            .insert(name.to_string());
+        Ok(())
+    }
+
+    /// declares `name` with `var` in every scope up to the enclosing function
+    pub fn declare_var(&mut self, name: &str, span: Span) -> Result<(), ParseError> {
+        for scope in self.scopes.iter_mut().rev() {
+            if scope.lexical.contains(name) {
+                return Err(redeclaration(name, span));
+            }
+            scope.var.insert(name.to_string());
+            if scope.is_function {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn redeclaration(name: &str, span: Span) -> ParseError {
+    ParseError::Redeclaration {
+        name: name.to_string(),
+        span,
+    }
+}
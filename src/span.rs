@@ -0,0 +1,72 @@
+/// a half-open range `[start, end)` of byte offsets into the source
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// maps byte offsets to 1-based line and column numbers
+pub struct LineIndex {
+    /// byte offset of the first char of every line
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        let mut chars = source.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\r' if matches!(chars.peek(), Some((_, '\n'))) => {}
+                '\r' | '\n' | '\u{2028}' | '\u{2029}' => line_starts.push(i + c.len_utf8()),
+                _ => {}
+            }
+        }
+        LineIndex { line_starts }
+    }
+
+    /// the line and column of `offset`, with the column counted in chars
+    pub fn line_col(&self, source: &str, offset: usize) -> (usize, usize) {
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let start = self.line_starts[line];
+        let column = source[start..offset.min(source.len())].chars().count();
+        (line + 1, column + 1)
+    }
+
+    /// the byte range of the 1-based `line`, without its line terminator
+    pub fn line_span(&self, source: &str, line: usize) -> Span {
+        let start = self.line_starts[line - 1];
+        let end = match self.line_starts.get(line) {
+            Some(&next) => next,
+            None => source.len(),
+        };
+        let text = source[start..end].trim_end_matches(['\r', '\n', '\u{2028}', '\u{2029}']);
+        Span::new(start, start + text.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_line_col() {
+        let source = "a\nbc\r\nπd\re";
+        let index = LineIndex::new(source);
+
+        assert_eq!(index.line_col(source, 0), (1, 1));
+        assert_eq!(index.line_col(source, 1), (1, 2));
+        assert_eq!(index.line_col(source, 3), (2, 2));
+        assert_eq!(index.line_col(source, 6), (3, 1));
+        assert_eq!(index.line_col(source, 8), (3, 2));
+        assert_eq!(index.line_col(source, 10), (4, 1));
+        assert_eq!(index.line_span(source, 2), Span::new(2, 4));
+        assert_eq!(index.line_span(source, 4), Span::new(10, 11));
+    }
+}
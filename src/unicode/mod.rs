@@ -0,0 +1,83 @@
+mod tables;
+
+/// whether `c` can start an identifier (`IdentifierStartChar`)
+pub fn is_id_start(c: char) -> bool {
+    match c {
+        '$' | '_' | 'a'..='z' | 'A'..='Z' => true,
+        c if c.is_ascii() => false,
+        c => in_table(tables::ID_START, c),
+    }
+}
+
+/// whether `c` can continue an identifier (`IdentifierPartChar`)
+pub fn is_id_continue(c: char) -> bool {
+    match c {
+        '$' | '_' | 'a'..='z' | 'A'..='Z' | '0'..='9' => true,
+        // ZWNJ and ZWJ
+        '\u{200C}' | '\u{200D}' => true,
+        c if c.is_ascii() => false,
+        c => in_table(tables::ID_CONTINUE, c),
+    }
+}
+
+/// whether `c` ends a line (`LineTerminator`)
+pub fn is_line_terminator(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+}
+
+/// whether `c` is `WhiteSpace`: tab, vertical tab, form feed, ZWNBSP or a space separator
+pub fn is_whitespace(c: char) -> bool {
+    match c {
+        '\t' | '\u{b}' | '\u{c}' | '\u{feff}' => true,
+        // `char::is_whitespace` also covers the line terminators and NEL
+        '\u{85}' => false,
+        c => c.is_whitespace() && !is_line_terminator(c),
+    }
+}
+
+fn in_table(table: &[(char, char)], c: char) -> bool {
+    table
+        .binary_search_by(|&(start, end)| {
+            if end < c {
+                std::cmp::Ordering::Less
+            } else if start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::unicode::{is_id_continue, is_id_start, is_whitespace};
+
+    #[test]
+    fn test_id_chars() {
+        for c in ['$', '_', 'é', 'π', '℘', '中', 'ǅ', 'Ⅻ'] {
+            assert!(is_id_start(c), "{c:?}");
+            assert!(is_id_continue(c), "{c:?}");
+        }
+        for c in ['0', '\u{301}', '٣', '‿', '·', '\u{200C}'] {
+            assert!(!is_id_start(c), "{c:?}");
+            assert!(is_id_continue(c), "{c:?}");
+        }
+        for c in ['-', ' ', '\u{a0}', '€', '☕', 'ⸯ', '\u{2028}'] {
+            assert!(!is_id_start(c), "{c:?}");
+            assert!(!is_id_continue(c), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn test_whitespace() {
+        for c in [
+            ' ', '\t', '\u{b}', '\u{c}', '\u{a0}', '\u{feff}', '\u{3000}',
+        ] {
+            assert!(is_whitespace(c), "{c:?}");
+        }
+        for c in ['\n', '\r', '\u{2028}', '\u{85}', '\u{200b}', 'a'] {
+            assert!(!is_whitespace(c), "{c:?}");
+        }
+    }
+}